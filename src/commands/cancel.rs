@@ -0,0 +1,56 @@
+use anyhow::bail;
+
+use crate::{
+    controllers::deployment::{cancel, get_deployment, get_linked_deployments, is_stable},
+    util::prompt,
+};
+
+use super::*;
+
+/// Cancel the in-progress deployment of the linked service
+#[derive(Parser)]
+pub struct Args {
+    /// The ID of the deployment to cancel, defaults to the latest in-progress one
+    #[clap(short, long)]
+    deployment: Option<String>,
+
+    /// Skip the confirmation prompt
+    #[clap(short, long)]
+    yes: bool,
+}
+
+pub async fn command(args: Args, _json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+
+    let deployment = match args.deployment {
+        Some(ref id) => get_deployment(&client, &configs, &linked_project.project, id).await?,
+        None => {
+            let Some(deployment) = get_linked_deployments(&client, &configs, &linked_project)
+                .await?
+                .into_iter()
+                .find(|deployment| !is_stable(&deployment.status))
+            else {
+                bail!("No deployment in progress");
+            };
+            deployment
+        }
+    };
+    if is_stable(&deployment.status) {
+        bail!(
+            "Deployment {} has already finished ({:?})",
+            deployment.id,
+            deployment.status
+        );
+    }
+    prompt::confirm(
+        &configs,
+        &format!("Cancel deployment {}?", deployment.id),
+        args.yes,
+    )?;
+
+    cancel(&client, &configs, &deployment.id).await?;
+    println!("Cancelled deployment {}", deployment.id.bold());
+    Ok(())
+}
@@ -0,0 +1,478 @@
+use std::{collections::BTreeSet, fmt::Display, path::PathBuf, time::Duration};
+
+use anyhow::bail;
+use clap::Subcommand;
+use serde::Serialize;
+use similar::TextDiff;
+
+use chrono::Utc;
+
+use crate::{
+    controllers::deployment::{
+        get_latest_deployment, get_linked_deployments, get_snapshot, wait_for_deployment,
+        Deployment,
+    },
+    util::archive,
+};
+
+use super::queries::deployments::DeploymentStatus;
+
+use super::*;
+
+/// List and inspect the deployments of the linked service
+#[derive(Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
+pub struct Args {
+    #[clap(subcommand)]
+    command: Option<Commands>,
+
+    #[clap(flatten)]
+    list: ListArgs,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// List recent deployments of the linked service (default)
+    List(ListArgs),
+
+    /// Download and extract the source snapshot a deployment was built from
+    Download(DownloadArgs),
+
+    /// Compare the source snapshots of two deployments
+    Diff(DiffArgs),
+
+    /// Wait for a deployment to finish, exiting with a code that reflects its status:
+    /// 0 success, 10 failed, 11 crashed, 12 removed, 13 skipped, 124 timed out
+    Wait(WaitArgs),
+}
+
+#[derive(Parser)]
+struct ListArgs {
+    /// Only show deployments with this status
+    #[clap(short, long, value_parser = STATUSES, ignore_case = true)]
+    status: Vec<String>,
+
+    /// Maximum number of deployments to show
+    #[clap(short, long, default_value_t = 10)]
+    limit: usize,
+}
+
+const STATUSES: [&str; 10] = [
+    "building",
+    "crashed",
+    "deploying",
+    "failed",
+    "initializing",
+    "removed",
+    "removing",
+    "skipped",
+    "success",
+    "waiting",
+];
+
+#[derive(Parser)]
+struct DownloadArgs {
+    /// The ID of the deployment
+    deployment_id: String,
+
+    /// Directory to extract the snapshot into, defaults to the deployment ID
+    #[clap(short, long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+struct DiffArgs {
+    /// The ID of the deployment to compare from
+    from: String,
+
+    /// The ID of the deployment to compare to
+    to: String,
+
+    /// Show a unified diff of changed text files
+    #[clap(short, long)]
+    patch: bool,
+}
+
+#[derive(Parser)]
+struct WaitArgs {
+    /// The ID of the deployment, defaults to the latest one of the linked service
+    deployment_id: Option<String>,
+
+    /// Give up after this many seconds
+    #[clap(short, long)]
+    timeout: Option<u64>,
+}
+
+/// Exit code of `deployments wait` when the timeout is reached, matching `timeout(1)`
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
+pub async fn command(args: Args, json: bool) -> Result<()> {
+    match args.command {
+        None => list(args.list, json).await,
+        Some(Commands::List(args)) => list(args, json).await,
+        Some(Commands::Download(args)) => download(args, json).await,
+        Some(Commands::Diff(args)) => diff(args, json).await,
+        Some(Commands::Wait(args)) => wait(args, json).await,
+    }
+}
+
+async fn list(args: ListArgs, json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+
+    let deployments: Vec<_> = get_linked_deployments(&client, &configs, &linked_project)
+        .await?
+        .into_iter()
+        .filter(|deployment| {
+            args.status.is_empty()
+                || args
+                    .status
+                    .iter()
+                    .any(|status| format!("{:?}", deployment.status).eq_ignore_ascii_case(status))
+        })
+        .take(args.limit)
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&deployments)?);
+        return Ok(());
+    }
+    if deployments.is_empty() {
+        println!("No deployments found");
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 6]> = deployments
+        .iter()
+        .map(|deployment| {
+            [
+                deployment.id.clone(),
+                status(&deployment.status),
+                age(deployment),
+                deployment
+                    .url
+                    .as_ref()
+                    .or(deployment.static_url.as_ref())
+                    .cloned()
+                    .unwrap_or_default(),
+                if deployment.can_rollback {
+                    "yes".to_owned()
+                } else {
+                    String::new()
+                },
+                commit(deployment),
+            ]
+        })
+        .collect();
+    let header = ["ID", "STATUS", "CREATED", "URL", "ROLLBACK", "COMMIT"]
+        .map(|name| name.bold().to_string());
+    let mut widths = [0; 6];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(console::measure_text_width(cell));
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| {
+                let padding = width - console::measure_text_width(cell);
+                format!("{cell}{}", " ".repeat(padding))
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+/// Print the status of a deployment that is being waited on
+pub fn print_status(status: &DeploymentStatus) {
+    println!("{} {}", "Deployment".bold(), self::status(status));
+}
+
+fn status(status: &DeploymentStatus) -> String {
+    let name = format!("{status:?}");
+    match status {
+        DeploymentStatus::SUCCESS => name.green().to_string(),
+        DeploymentStatus::FAILED | DeploymentStatus::CRASHED => name.red().to_string(),
+        DeploymentStatus::BUILDING
+        | DeploymentStatus::DEPLOYING
+        | DeploymentStatus::INITIALIZING
+        | DeploymentStatus::WAITING => name.yellow().to_string(),
+        _ => name.dimmed().to_string(),
+    }
+}
+
+/// How long ago a deployment was created, e.g. `5m ago`
+pub fn age(deployment: &Deployment) -> String {
+    let age = Utc::now() - deployment.created_at;
+    if age.num_days() > 0 {
+        format!("{}d ago", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h ago", age.num_hours())
+    } else if age.num_minutes() > 0 {
+        format!("{}m ago", age.num_minutes())
+    } else {
+        "just now".to_owned()
+    }
+}
+
+/// The commit a deployment was built from, as recorded in its metadata
+pub fn commit(deployment: &Deployment) -> String {
+    let Some(ref meta) = deployment.meta else {
+        return String::new();
+    };
+    let field = |name: &str| meta.get(name).and_then(|value| value.as_str());
+    let mut commit = vec![];
+    if let Some(hash) = field("commitHash") {
+        commit.push(hash[..hash.len().min(7)].to_owned());
+    }
+    if let Some(branch) = field("branch") {
+        commit.push(format!("({branch})"));
+    }
+    if let Some(message) = field("commitMessage").and_then(|message| message.lines().next()) {
+        commit.push(message.dimmed().to_string());
+    }
+    commit.join(" ")
+}
+
+async fn download(args: DownloadArgs, json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let out = args
+        .out
+        .unwrap_or_else(|| PathBuf::from(&args.deployment_id));
+    if out.exists() && std::fs::read_dir(&out)?.next().is_some() {
+        bail!("{} already exists and is not empty", out.display());
+    }
+
+    let snapshot = get_snapshot(&client, &configs, &args.deployment_id).await?;
+    let files = archive::extract(snapshot.as_slice(), &out)?;
+
+    if json {
+        let download = Download {
+            deployment_id: args.deployment_id,
+            path: out.display().to_string(),
+            files,
+        };
+        println!("{}", serde_json::to_string_pretty(&download)?);
+    } else {
+        println!(
+            "Extracted {} files from deployment {} into {}",
+            files,
+            args.deployment_id.bold(),
+            out.display().to_string().bold()
+        );
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Download {
+    deployment_id: String,
+    path: String,
+    files: usize,
+}
+
+async fn diff(args: DiffArgs, json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let (from, to) = tokio::try_join!(
+        get_snapshot(&client, &configs, &args.from),
+        get_snapshot(&client, &configs, &args.to)
+    )?;
+    let from = archive::read_files(from.as_slice())?;
+    let to = archive::read_files(to.as_slice())?;
+
+    let names: BTreeSet<_> = from.keys().chain(to.keys()).collect();
+    let mut changes = vec![];
+    for name in names {
+        let (old, new) = (from.get(name), to.get(name));
+        if old == new {
+            continue;
+        }
+        let kind = match (old, new) {
+            (None, Some(_)) => ChangeKind::Added,
+            (Some(_), None) => ChangeKind::Removed,
+            _ => ChangeKind::Modified,
+        };
+        // Added and removed files are diffed against an empty file
+        let patch = if args.patch {
+            text_diff(
+                &name.display().to_string(),
+                old.map(Vec::as_slice).unwrap_or_default(),
+                new.map(Vec::as_slice).unwrap_or_default(),
+            )
+        } else {
+            None
+        };
+        changes.push(Change {
+            path: name.display().to_string(),
+            kind,
+            old_size: old.map(Vec::len),
+            new_size: new.map(Vec::len),
+            patch,
+        });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+        return Ok(());
+    }
+    if changes.is_empty() {
+        println!(
+            "No changes between {} and {}",
+            args.from.bold(),
+            args.to.bold()
+        );
+        return Ok(());
+    }
+    for change in &changes {
+        println!("{change}");
+    }
+    for patch in changes.iter().filter_map(|change| change.patch.as_ref()) {
+        println!();
+        for line in patch.lines() {
+            if line.starts_with("+++") || line.starts_with("---") {
+                println!("{}", line.bold());
+            } else if line.starts_with('+') {
+                println!("{}", line.green());
+            } else if line.starts_with('-') {
+                println!("{}", line.red());
+            } else if line.starts_with("@@") {
+                println!("{}", line.cyan());
+            } else {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn wait(args: WaitArgs, json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+    let deployment_id = match args.deployment_id {
+        Some(id) => id,
+        None => {
+            get_latest_deployment(&client, &configs, &linked_project)
+                .await?
+                .id
+        }
+    };
+
+    let wait = wait_for_deployment(
+        &client,
+        &configs,
+        &linked_project.project,
+        &deployment_id,
+        |status| {
+            if !json {
+                print_status(status);
+            }
+        },
+    );
+    let status = match args.timeout {
+        Some(timeout) => match tokio::time::timeout(Duration::from_secs(timeout), wait).await {
+            Ok(status) => status?,
+            Err(_) => {
+                eprintln!("Timed out waiting for deployment {deployment_id}");
+                std::process::exit(TIMEOUT_EXIT_CODE);
+            }
+        },
+        None => wait.await?,
+    };
+
+    if json {
+        let result = WaitResult {
+            deployment_id: &deployment_id,
+            status: &status,
+        };
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    let code = match status {
+        DeploymentStatus::SUCCESS => 0,
+        DeploymentStatus::FAILED => 10,
+        DeploymentStatus::CRASHED => 11,
+        DeploymentStatus::REMOVED => 12,
+        DeploymentStatus::SKIPPED => 13,
+        _ => 1,
+    };
+    std::process::exit(code);
+}
+
+/// A unified diff of two versions of a file, or `None` if either isn't text
+fn text_diff(name: &str, old: &[u8], new: &[u8]) -> Option<String> {
+    let old = std::str::from_utf8(old)
+        .ok()
+        .filter(|s| !s.contains('\0'))?;
+    let new = std::str::from_utf8(new)
+        .ok()
+        .filter(|s| !s.contains('\0'))?;
+    Some(
+        TextDiff::from_lines(old, new)
+            .unified_diff()
+            .header(&format!("a/{name}"), &format!("b/{name}"))
+            .to_string(),
+    )
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WaitResult<'a> {
+    deployment_id: &'a str,
+    status: &'a DeploymentStatus,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Change {
+    path: String,
+    kind: ChangeKind,
+    old_size: Option<usize>,
+    new_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    patch: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ChangeKind::Added => write!(
+                f,
+                "{} {} ({} bytes)",
+                "A".green().bold(),
+                self.path,
+                self.new_size.unwrap_or_default()
+            ),
+            ChangeKind::Removed => write!(
+                f,
+                "{} {} ({} bytes)",
+                "D".red().bold(),
+                self.path,
+                self.old_size.unwrap_or_default()
+            ),
+            ChangeKind::Modified => write!(
+                f,
+                "{} {} ({} -> {} bytes)",
+                "M".yellow().bold(),
+                self.path,
+                self.old_size.unwrap_or_default(),
+                self.new_size.unwrap_or_default()
+            ),
+        }
+    }
+}
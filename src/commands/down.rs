@@ -0,0 +1,52 @@
+use anyhow::bail;
+
+use crate::{
+    controllers::deployment::{get_deployment, get_linked_deployments, remove},
+    util::prompt,
+};
+
+use super::{queries::deployments::DeploymentStatus, *};
+
+/// Remove the active deployment of the linked service, taking it offline
+#[derive(Parser)]
+pub struct Args {
+    /// The ID of the deployment to remove, defaults to the active one
+    #[clap(short, long)]
+    deployment: Option<String>,
+
+    /// Skip the confirmation prompt
+    #[clap(short, long)]
+    yes: bool,
+}
+
+pub async fn command(args: Args, _json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+
+    let deployment = match args.deployment {
+        Some(ref id) => get_deployment(&client, &configs, &linked_project.project, id).await?,
+        None => {
+            let Some(deployment) = get_linked_deployments(&client, &configs, &linked_project)
+                .await?
+                .into_iter()
+                .find(|deployment| matches!(deployment.status, DeploymentStatus::SUCCESS))
+            else {
+                bail!("No active deployment found");
+            };
+            deployment
+        }
+    };
+    prompt::confirm(
+        &configs,
+        &format!(
+            "Remove deployment {}? This takes the service offline",
+            deployment.id
+        ),
+        args.yes,
+    )?;
+
+    remove(&client, &configs, &deployment.id).await?;
+    println!("Removed deployment {}", deployment.id.bold());
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Duration, Utc};
+use clap::Subcommand;
+use serde::Serialize;
+
+use crate::controllers::deployment::{get_linked_deployments, Deployment};
+
+use super::{deployments::age, *};
+
+/// Inspect the GitHub integration
+#[derive(Parser)]
+pub struct Args {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Show recent GitHub events and the deployments of the linked service that followed them
+    Events(EventsArgs),
+}
+
+#[derive(Parser)]
+struct EventsArgs {
+    /// Maximum number of events to show
+    #[clap(short, long, default_value_t = 20)]
+    limit: usize,
+}
+
+/// How long after an event a deployment is considered to have been triggered by it
+const TRIGGER_WINDOW_MINUTES: i64 = 10;
+
+pub async fn command(args: Args, json: bool) -> Result<()> {
+    match args.command {
+        Commands::Events(args) => events(args, json).await,
+    }
+}
+
+async fn events(args: EventsArgs, json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+
+    let vars = queries::user_meta::Variables {};
+    let res = post_graphql::<queries::UserMeta, _>(&client, configs.get_backboard(), vars).await?;
+    let me = res.data.context("Failed to retrieve user")?.me;
+
+    let vars = queries::git_hub_events::Variables { user_id: me.id };
+    let res =
+        post_graphql::<queries::GitHubEvents, _>(&client, configs.get_backboard(), vars).await?;
+    let mut github_events = res
+        .data
+        .context("Failed to retrieve GitHub events")?
+        .github_events;
+    github_events.sort_by_key(|event| std::cmp::Reverse(event.created_at));
+    github_events.truncate(args.limit);
+
+    let deployments = get_linked_deployments(&client, &configs, &linked_project).await?;
+
+    // Events come without a link to what they triggered, so match deployments by time.
+    // Events are newest first, so the previous event bounds the window of the current one.
+    let mut events = vec![];
+    let mut next_event: Option<DateTime<Utc>> = None;
+    for event in github_events {
+        let triggered = match event.created_at {
+            Some(created_at) => {
+                let until = next_event
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC)
+                    .min(created_at + Duration::minutes(TRIGGER_WINDOW_MINUTES));
+                next_event = Some(created_at);
+                deployments
+                    .iter()
+                    .filter(|d| d.created_at >= created_at && d.created_at < until)
+                    .cloned()
+                    .collect()
+            }
+            None => vec![],
+        };
+        events.push(Event {
+            created_at: event.created_at,
+            kind: event.type_,
+            deployments: triggered,
+        });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&events)?);
+        return Ok(());
+    }
+    if events.is_empty() {
+        println!("No GitHub events found");
+        return Ok(());
+    }
+    for event in events {
+        let created_at = event
+            .created_at
+            .map(|created_at| created_at.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        println!("{} {}", created_at.dimmed(), event.kind.bold());
+        if event.deployments.is_empty() {
+            println!("  {}", "No deployment followed".dimmed());
+        }
+        for deployment in event.deployments {
+            println!(
+                "  {} {} {:?} {}",
+                "→".green(),
+                deployment.id,
+                deployment.status,
+                age(&deployment).dimmed()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Event {
+    created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "type")]
+    kind: String,
+    deployments: Vec<Deployment>,
+}
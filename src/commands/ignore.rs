@@ -0,0 +1,284 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use ::ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    Match,
+};
+use anyhow::bail;
+use clap::Subcommand;
+use serde::Serialize;
+
+use crate::util::archive;
+
+use super::*;
+
+/// Inspect which files `railway up` uploads
+#[derive(Parser)]
+pub struct Args {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Explain whether a path would be uploaded, and which ignore rule decided it
+    Check(CheckArgs),
+}
+
+#[derive(Parser)]
+struct CheckArgs {
+    /// The path to check
+    #[clap(required_unless_present = "all_ignored")]
+    path: Option<PathBuf>,
+
+    /// List every ignored file and directory instead
+    #[clap(long, conflicts_with = "path")]
+    all_ignored: bool,
+}
+
+/// The directory `railway up` uploads when no path is given
+const ROOT: &str = ".";
+
+/// Ignore files in the order the walker gives them precedence
+const IGNORE_FILES: &[&str] = &[".railwayignore", ".ignore"];
+
+pub async fn command(args: Args, json: bool) -> Result<()> {
+    match args.command {
+        Commands::Check(args) => match args.path {
+            Some(path) => check(&path, json),
+            None => all_ignored(json),
+        },
+    }
+}
+
+fn check(path: &Path, json: bool) -> Result<()> {
+    let root = Path::new(ROOT);
+    let canonical_root = root.canonicalize()?;
+    let name = archive_name(path, &canonical_root)?;
+    let target = root.join(&name);
+
+    // Let the walker `up` uses decide, only descending towards the target
+    let filter_target = target.clone();
+    let uploaded = archive::walk_builder(root, false)
+        .filter_entry(move |entry| filter_target.starts_with(entry.path()))
+        .build()
+        .filter_map(|entry| entry.ok())
+        .any(|entry| entry.path() == target);
+
+    // Walk down from the root to find the rule that decided it. An ignored directory
+    // excludes everything inside of it.
+    let mut rule = None;
+    let mut current = canonical_root.clone();
+    for component in name.components() {
+        current.push(component);
+        rule = explain(&current, current.is_dir())?;
+        if matches!(rule, Some(ref rule) if !rule.whitelist) {
+            break;
+        }
+    }
+
+    let verdict = Verdict {
+        path: name.display().to_string(),
+        uploaded,
+        rule,
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&verdict)?);
+    } else {
+        println!("{verdict}");
+    }
+    Ok(())
+}
+
+fn all_ignored(json: bool) -> Result<()> {
+    let root = Path::new(ROOT);
+    let canonical_root = root.canonicalize()?;
+    let uploaded = archive::walk_builder(root, false)
+        .build()
+        .map(|entry| Ok(entry?.into_path()))
+        .collect::<Result<HashSet<_>>>()?;
+
+    // Only report the topmost ignored entry, without descending into ignored directories
+    let mut ignored = vec![];
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let is_dir = entry.file_type()?.is_dir();
+            if !uploaded.contains(&path) {
+                let name = path.strip_prefix(root)?.to_path_buf();
+                ignored.push(Verdict {
+                    path: name.display().to_string(),
+                    uploaded: false,
+                    rule: explain(&canonical_root.join(&name), is_dir)?,
+                });
+            } else if is_dir {
+                dirs.push(path);
+            }
+        }
+    }
+    ignored.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&ignored)?);
+    } else if ignored.is_empty() {
+        println!("Nothing is ignored");
+    } else {
+        for verdict in ignored {
+            match verdict.rule {
+                Some(rule) => println!("{} {}", verdict.path.bold(), rule.to_string().dimmed()),
+                None => println!("{}", verdict.path.bold()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Get the path of `path` relative to the upload root
+fn archive_name(path: &Path, canonical_root: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Invalid path {}", path.display()))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new(ROOT),
+    };
+    let parent = parent
+        .canonicalize()
+        .with_context(|| format!("{} does not exist", path.display()))?;
+    let Ok(name) = parent
+        .join(file_name)
+        .strip_prefix(canonical_root)
+        .map(Path::to_path_buf)
+    else {
+        bail!("{} is outside of the current directory", path.display());
+    };
+    Ok(name)
+}
+
+/// Find the ignore rule that matches the absolute `path`, in the same order of
+/// precedence the walker uses. Rules in deeper directories win over shallower
+/// ones of the same kind.
+fn explain(path: &Path, is_dir: bool) -> Result<Option<Rule>> {
+    let dirs: Vec<_> = path.ancestors().skip(1).collect();
+
+    for file in IGNORE_FILES {
+        for dir in &dirs {
+            if let Some(rule) = match_file(&dir.join(file), dir, path, is_dir)? {
+                return Ok(Some(rule));
+            }
+        }
+    }
+
+    // Git ignore rules only apply inside of a git repository
+    let Some(git_root) = dirs.iter().find(|dir| dir.join(".git").exists()) else {
+        return Ok(None);
+    };
+    for dir in dirs.iter().take_while(|dir| dir.starts_with(git_root)) {
+        if let Some(rule) = match_file(&dir.join(".gitignore"), dir, path, is_dir)? {
+            return Ok(Some(rule));
+        }
+    }
+    let exclude = git_root.join(".git").join("info").join("exclude");
+    if let Some(rule) = match_file(&exclude, git_root, path, is_dir)? {
+        return Ok(Some(rule));
+    }
+    let (global, _) = Gitignore::global();
+    Ok(to_rule(global.matched(path, is_dir)))
+}
+
+fn match_file(file: &Path, dir: &Path, path: &Path, is_dir: bool) -> Result<Option<Rule>> {
+    if !file.is_file() {
+        return Ok(None);
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    if let Some(err) = builder.add(file) {
+        bail!("Failed to parse {}: {err}", file.display());
+    }
+    let gitignore = builder.build()?;
+    Ok(to_rule(gitignore.matched(path, is_dir)))
+}
+
+fn to_rule(matched: Match<&::ignore::gitignore::Glob>) -> Option<Rule> {
+    let glob = matched.inner()?;
+    let file = glob.from().map(Path::to_path_buf);
+    let current_dir = std::env::current_dir().and_then(|dir| dir.canonicalize());
+    // Later lines take precedence, so the last identical line is the one that matched
+    let line = file.as_ref().and_then(|file| {
+        let contents = std::fs::read_to_string(file).ok()?;
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.trim_end() == glob.original())
+            .last()
+            .map(|(index, _)| index + 1)
+    });
+    Some(Rule {
+        file: file.map(|file| match current_dir {
+            Ok(ref dir) => file
+                .strip_prefix(dir)
+                .unwrap_or(&file)
+                .display()
+                .to_string(),
+            Err(_) => file.display().to_string(),
+        }),
+        line,
+        pattern: glob.original().to_owned(),
+        whitelist: glob.is_whitelist(),
+    })
+}
+
+#[derive(Serialize)]
+struct Verdict {
+    path: String,
+    uploaded: bool,
+    rule: Option<Rule>,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.uploaded {
+            write!(
+                f,
+                "{} would be {}",
+                self.path.bold(),
+                "uploaded".green().bold()
+            )?;
+        } else {
+            write!(
+                f,
+                "{} would be {}",
+                self.path.bold(),
+                "ignored".red().bold()
+            )?;
+        }
+        match self.rule {
+            Some(ref rule) if rule.whitelist == self.uploaded => write!(f, "\n  {rule}"),
+            _ => write!(f, "\n  No ignore rule matches it"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Rule {
+    file: Option<String>,
+    line: Option<usize>,
+    pattern: String,
+    whitelist: bool,
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = match (&self.file, self.line) {
+            (Some(file), Some(line)) => format!("{file}:{line}"),
+            (Some(file), None) => file.clone(),
+            _ => "global gitignore".to_owned(),
+        };
+        write!(f, "{source}: {}", self.pattern)
+    }
+}
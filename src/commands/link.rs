@@ -93,7 +93,7 @@ pub async fn command(args: Args, _json: bool) -> Result<()> {
         .iter()
         .map(|project| &project.node)
         .collect();
-    personal_projects.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    personal_projects.sort_by_key(|p| std::cmp::Reverse(p.updated_at));
 
     let personal_project_names = personal_projects
         .iter()
@@ -148,7 +148,7 @@ pub async fn command(args: Args, _json: bool) -> Result<()> {
                 .iter()
                 .map(|project| &project.node)
                 .collect();
-            projects.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+            projects.sort_by_key(|p| std::cmp::Reverse(p.updated_at));
 
             let project_names = projects
                 .iter()
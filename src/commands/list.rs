@@ -30,7 +30,7 @@ pub async fn command(_args: Args, json: bool) -> Result<()> {
         .iter()
         .map(|project| &project.node)
         .collect();
-    my_projects.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    my_projects.sort_by_key(|p| std::cmp::Reverse(p.updated_at));
 
     let mut all_projects: Vec<_> = my_projects
         .iter()
@@ -72,7 +72,7 @@ pub async fn command(_args: Args, json: bool) -> Result<()> {
                 .iter()
                 .map(|project| &project.node)
                 .collect();
-            projects.sort_by(|a, b| a.updated_at.cmp(&b.updated_at));
+            projects.sort_by_key(|p| p.updated_at);
             let mut team_projects: Vec<_> = projects
                 .iter()
                 .map(|project| Project::Team((*project).clone()))
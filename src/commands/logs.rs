@@ -1,6 +1,8 @@
 use futures::StreamExt;
 
-use crate::subscription::subscribe_graphql;
+use crate::{
+    controllers::deployment::get_latest_deployment, subscription::subscribe_graphql, util::ci,
+};
 
 use super::*;
 
@@ -21,24 +23,7 @@ pub async fn command(args: Args, json: bool) -> Result<()> {
     let client = GQLClient::new_authorized(&configs)?;
     let linked_project = configs.get_linked_project().await?;
 
-    let vars = queries::deployments::Variables {
-        project_id: linked_project.project.clone(),
-    };
-
-    let res =
-        post_graphql::<queries::Deployments, _>(&client, configs.get_backboard(), vars).await?;
-
-    let body = res.data.context("Failed to retrieve response body")?;
-
-    let mut deployments: Vec<_> = body
-        .project
-        .deployments
-        .edges
-        .into_iter()
-        .map(|deployment| deployment.node)
-        .collect();
-    deployments.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-    let latest_deployment = deployments.first().context("No deployments found")?;
+    let latest_deployment = get_latest_deployment(&client, &configs, &linked_project).await?;
 
     if args.build && !args.deployment {
         let vars = subscriptions::build_logs::Variables {
@@ -48,6 +33,9 @@ pub async fn command(args: Args, json: bool) -> Result<()> {
         };
 
         let (_client, mut log_stream) = subscribe_graphql::<subscriptions::BuildLogs>(vars).await?;
+        if !json {
+            ci::start_group("Build Logs");
+        }
         while let Some(Ok(log)) = log_stream.next().await {
             let log = log.data.context("Failed to retrieve log")?;
             for line in log.build_logs {
@@ -67,6 +55,9 @@ pub async fn command(args: Args, json: bool) -> Result<()> {
 
         let (_client, mut log_stream) =
             subscribe_graphql::<subscriptions::DeploymentLogs>(vars).await?;
+        if !json {
+            ci::start_group("Deploy Logs");
+        }
         while let Some(Ok(log)) = log_stream.next().await {
             let log = log.data.context("Failed to retrieve log")?;
             for line in log.deployment_logs {
@@ -78,6 +69,9 @@ pub async fn command(args: Args, json: bool) -> Result<()> {
             }
         }
     }
+    if !json {
+        ci::end_group();
+    }
 
     Ok(())
 }
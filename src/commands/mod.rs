@@ -4,11 +4,16 @@ pub(super) use clap::Parser;
 pub(super) use colored::Colorize;
 
 pub mod add;
+pub mod cancel;
 pub mod completion;
 pub mod delete;
+pub mod deployments;
 pub mod docs;
 pub mod domain;
+pub mod down;
 pub mod environment;
+pub mod github;
+pub mod ignore;
 pub mod init;
 pub mod link;
 pub mod list;
@@ -16,11 +21,15 @@ pub mod login;
 pub mod logout;
 pub mod logs;
 pub mod open;
+pub mod redeploy;
+pub mod restart;
+pub mod rollback;
 pub mod run;
 pub mod service;
 pub mod shell;
 pub mod starship;
 pub mod status;
+pub mod triggers;
 pub mod unlink;
 pub mod up;
 pub mod variables;
@@ -0,0 +1,65 @@
+use anyhow::bail;
+
+use crate::{
+    controllers::deployment::{
+        get_deployment, get_latest_deployment, is_failure, redeploy, wait_for_deployment,
+    },
+    util::prompt,
+};
+
+use super::{deployments::print_status, *};
+
+/// Redeploy the latest deployment of the linked service
+#[derive(Parser)]
+pub struct Args {
+    /// The ID of the deployment to redeploy, defaults to the latest one
+    #[clap(short, long)]
+    deployment: Option<String>,
+
+    /// Skip the confirmation prompt
+    #[clap(short, long)]
+    yes: bool,
+
+    /// Wait for the new deployment to finish
+    #[clap(short, long)]
+    wait: bool,
+}
+
+pub async fn command(args: Args, _json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+
+    let deployment = match args.deployment {
+        Some(ref id) => get_deployment(&client, &configs, &linked_project.project, id).await?,
+        None => get_latest_deployment(&client, &configs, &linked_project).await?,
+    };
+    prompt::confirm(
+        &configs,
+        &format!("Redeploy deployment {}?", deployment.id),
+        args.yes,
+    )?;
+
+    let deployment_id = redeploy(&client, &configs, &deployment.id).await?;
+    println!(
+        "Redeploying {} as {}",
+        deployment.id.bold(),
+        deployment_id.bold()
+    );
+
+    if args.wait {
+        println!("Waiting for deployment {} to finish", deployment_id.bold());
+        let status = wait_for_deployment(
+            &client,
+            &configs,
+            &linked_project.project,
+            &deployment_id,
+            print_status,
+        )
+        .await?;
+        if is_failure(&status) {
+            bail!("Deployment {deployment_id} {status:?}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use anyhow::bail;
+
+use crate::{
+    controllers::deployment::{
+        get_deployment, get_latest_deployment, is_failure, restart, wait_for_restart,
+    },
+    util::prompt,
+};
+
+use super::{deployments::print_status, *};
+
+/// Restart the latest deployment of the linked service without rebuilding it
+#[derive(Parser)]
+pub struct Args {
+    /// The ID of the deployment to restart, defaults to the latest one
+    #[clap(short, long)]
+    deployment: Option<String>,
+
+    /// Skip the confirmation prompt
+    #[clap(short, long)]
+    yes: bool,
+
+    /// Wait for the deployment to be running again
+    #[clap(short, long)]
+    wait: bool,
+}
+
+pub async fn command(args: Args, _json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+
+    let deployment = match args.deployment {
+        Some(ref id) => get_deployment(&client, &configs, &linked_project.project, id).await?,
+        None => get_latest_deployment(&client, &configs, &linked_project).await?,
+    };
+    prompt::confirm(
+        &configs,
+        &format!("Restart deployment {}?", deployment.id),
+        args.yes,
+    )?;
+
+    restart(&client, &configs, &deployment.id).await?;
+    println!("Restarting {}", deployment.id.bold());
+
+    if args.wait {
+        println!("Waiting for deployment {} to finish", deployment.id.bold());
+        let status = wait_for_restart(
+            &client,
+            &configs,
+            &linked_project.project,
+            &deployment.id,
+            &deployment.status,
+            print_status,
+        )
+        .await?;
+        if is_failure(&status) {
+            bail!("Deployment {} {status:?}", deployment.id);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::{collections::HashSet, fmt::Display};
+
+use anyhow::bail;
+use is_terminal::IsTerminal;
+
+use crate::{
+    consts::NON_INTERACTIVE_FAILURE,
+    controllers::deployment::{
+        get_deployment, get_linked_deployment_ids, get_linked_deployments, is_failure, rollback,
+        wait_for_deployment, wait_for_new_deployment, Deployment,
+    },
+    util::prompt,
+};
+
+use super::{
+    deployments::{age, commit, print_status},
+    *,
+};
+
+/// Roll back the linked service to a previous deployment
+#[derive(Parser)]
+pub struct Args {
+    /// The ID of the deployment to roll back to. Prompts for one if omitted.
+    deployment_id: Option<String>,
+
+    /// Skip the confirmation prompt
+    #[clap(short, long)]
+    yes: bool,
+
+    /// Don't wait for the rollback to finish
+    #[clap(short, long)]
+    detach: bool,
+}
+
+pub async fn command(args: Args, _json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+
+    let deployment = match args.deployment_id {
+        Some(ref id) => {
+            let deployment = get_deployment(&client, &configs, &linked_project.project, id).await?;
+            if !deployment.can_rollback {
+                bail!("Deployment {id} can't be rolled back to");
+            }
+            prompt::confirm(
+                &configs,
+                &format!("Roll back to deployment {id}?"),
+                args.yes,
+            )?;
+            deployment
+        }
+        None => {
+            if !std::io::stdout().is_terminal() {
+                bail!(NON_INTERACTIVE_FAILURE);
+            }
+            let deployments: Vec<_> = get_linked_deployments(&client, &configs, &linked_project)
+                .await?
+                .into_iter()
+                .filter(|deployment| deployment.can_rollback)
+                .map(Rollback)
+                .collect();
+            if deployments.is_empty() {
+                bail!("No deployments to roll back to");
+            }
+            inquire::Select::new("Select a deployment to roll back to", deployments)
+                .with_render_config(configs.get_render_config())
+                .prompt()?
+                .0
+        }
+    };
+
+    // The rollback creates a new deployment, which is the one that isn't known yet
+    let known = if args.detach {
+        HashSet::new()
+    } else {
+        get_linked_deployment_ids(&client, &configs, &linked_project).await?
+    };
+    rollback(&client, &configs, &deployment.id).await?;
+    println!("Rolling back to {}", deployment.id.bold());
+    if args.detach {
+        return Ok(());
+    }
+
+    let Some(new_deployment) =
+        wait_for_new_deployment(&client, &configs, &linked_project, &known).await?
+    else {
+        println!("Unable to find the deployment for this rollback");
+        return Ok(());
+    };
+    println!(
+        "Waiting for deployment {} to finish",
+        new_deployment.id.bold()
+    );
+    let status = wait_for_deployment(
+        &client,
+        &configs,
+        &linked_project.project,
+        &new_deployment.id,
+        print_status,
+    )
+    .await?;
+    if is_failure(&status) {
+        bail!("Deployment {} {status:?}", new_deployment.id);
+    }
+    Ok(())
+}
+
+struct Rollback(Deployment);
+
+impl Display for Rollback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.0.id, age(&self.0), commit(&self.0))
+    }
+}
@@ -1,19 +1,77 @@
 use std::fmt::Display;
 
 use anyhow::bail;
+use clap::Subcommand;
 
-use crate::consts::SERVICE_NOT_FOUND;
+use crate::{
+    consts::{SERVICE_NOT_FOUND, SERVICE_NOT_LINKED},
+    controllers::github::{
+        create_trigger, delete_trigger, get_branches, get_triggers, update_trigger_branch,
+        validate_branch,
+    },
+    util::prompt,
+};
 
-use super::{queries::project::ProjectProjectServicesEdgesNode, *};
+use super::{queries::project::ProjectProjectServicesEdgesNode, triggers::choose_branch, *};
 
 /// Link a service to the current project
 #[derive(Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
 pub struct Args {
+    #[clap(subcommand)]
+    command: Option<Commands>,
+
     /// The service to link
     service: Option<String>,
 }
 
-pub async fn command(args: Args, _json: bool) -> Result<()> {
+#[derive(Subcommand)]
+enum Commands {
+    /// Create a service in the linked project and link it
+    Create(CreateArgs),
+
+    /// Deploy the linked service from a GitHub repository
+    Connect(ConnectArgs),
+}
+
+#[derive(Parser)]
+struct CreateArgs {
+    /// The name of the service
+    name: String,
+
+    /// Deploy the service from a GitHub repository, as owner/name
+    #[clap(short, long)]
+    repo: Option<String>,
+
+    /// The branch of the repository to deploy, defaults to its default branch
+    #[clap(short, long, requires = "repo")]
+    branch: Option<String>,
+}
+
+#[derive(Parser)]
+struct ConnectArgs {
+    /// The GitHub repository, as owner/name
+    #[clap(short, long)]
+    repo: String,
+
+    /// The branch to deploy. Prompts for one if omitted.
+    #[clap(short, long)]
+    branch: Option<String>,
+
+    /// Skip the confirmation prompt when removing triggers for other repositories
+    #[clap(short, long)]
+    yes: bool,
+}
+
+pub async fn command(args: Args, json: bool) -> Result<()> {
+    match args.command {
+        Some(Commands::Create(args)) => create(args, json).await,
+        Some(Commands::Connect(args)) => connect(args).await,
+        None => link(args.service).await,
+    }
+}
+
+async fn link(service: Option<String>) -> Result<()> {
     let mut configs = Configs::new()?;
     let client = GQLClient::new_authorized(&configs)?;
     let linked_project = configs.get_linked_project().await?;
@@ -34,7 +92,7 @@ pub async fn command(args: Args, _json: bool) -> Result<()> {
         .map(|env| Service(&env.node))
         .collect();
 
-    if let Some(service) = args.service {
+    if let Some(service) = service {
         let service = services
             .iter()
             .find(|env| env.0.id == service || env.0.name == service)
@@ -70,6 +128,110 @@ pub async fn command(args: Args, _json: bool) -> Result<()> {
     Ok(())
 }
 
+async fn create(args: CreateArgs, json: bool) -> Result<()> {
+    let mut configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+
+    // Check the repository is accessible before creating anything
+    match (&args.repo, &args.branch) {
+        (Some(repo), Some(branch)) => validate_branch(&client, &configs, repo, branch).await?,
+        (Some(repo), None) => {
+            get_branches(&client, &configs, repo).await?;
+        }
+        _ => {}
+    }
+    let vars = mutations::service_create::Variables {
+        project_id: linked_project.project.clone(),
+        name: args.name,
+        source: args
+            .repo
+            .map(|repo| mutations::service_create::ServiceSourceInput { repo }),
+        branch: args.branch,
+    };
+    let res =
+        post_graphql::<mutations::ServiceCreate, _>(&client, configs.get_backboard(), vars).await?;
+    let service = res.data.context("Failed to create service")?.service_create;
+
+    configs.link_service(service.id.clone())?;
+    configs.write()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&service)?);
+    } else {
+        println!("Created and linked service {}", service.name.bold());
+    }
+    Ok(())
+}
+
+async fn connect(args: ConnectArgs) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+    let Some(ref service) = linked_project.service else {
+        bail!(SERVICE_NOT_LINKED);
+    };
+
+    let branch = choose_branch(&client, &configs, &args.repo, args.branch).await?;
+
+    // Triggers for the previous repository would keep deploying it on push
+    let triggers = get_triggers(&client, &configs, &linked_project, service).await?;
+    let (current, stale): (Vec<_>, Vec<_>) = triggers
+        .into_iter()
+        .partition(|trigger| trigger.repository == args.repo);
+    if !stale.is_empty() {
+        let repos = stale
+            .iter()
+            .map(|trigger| format!("{}@{}", trigger.repository, trigger.branch))
+            .collect::<Vec<_>>()
+            .join(", ");
+        prompt::confirm(
+            &configs,
+            &format!("Remove the deploy triggers for {repos}?"),
+            args.yes,
+        )?;
+    }
+
+    let vars = mutations::service_instance_source_update::Variables {
+        service_id: service.clone(),
+        repo: args.repo.clone(),
+    };
+    post_graphql::<mutations::ServiceInstanceSourceUpdate, _>(
+        &client,
+        configs.get_backboard(),
+        vars,
+    )
+    .await?;
+
+    for trigger in &stale {
+        delete_trigger(&client, &configs, &trigger.id).await?;
+    }
+
+    // The source only holds the repository, the branch to deploy lives in a trigger
+    match current.first() {
+        Some(trigger) => update_trigger_branch(&client, &configs, &trigger.id, &branch).await?,
+        None => {
+            create_trigger(
+                &client,
+                &configs,
+                &linked_project,
+                service,
+                &args.repo,
+                &branch,
+                false,
+            )
+            .await?;
+        }
+    }
+
+    println!(
+        "Connected to {} on branch {}",
+        args.repo.bold(),
+        branch.purple()
+    );
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 struct Service<'a>(&'a ProjectProjectServicesEdgesNode);
 
@@ -44,7 +44,28 @@ pub async fn command(_args: Args, json: bool) -> Result<()> {
             .iter()
             .map(|service| &service.node)
         {
-            println!("{}", service.name.dimmed().bold());
+            let repo = service
+                .service_instances
+                .edges
+                .iter()
+                .map(|instance| &instance.node)
+                .find(|instance| instance.environment_id == linked_project.environment)
+                .and_then(|instance| instance.source.as_ref())
+                .and_then(|source| source.repo.as_ref());
+            let branch = service
+                .repo_triggers
+                .edges
+                .iter()
+                .map(|trigger| &trigger.node)
+                .find(|trigger| trigger.environment_id == linked_project.environment)
+                .map(|trigger| &trigger.branch);
+            match (repo, branch) {
+                (Some(repo), Some(branch)) => {
+                    println!("{} ({repo}@{branch})", service.name.dimmed().bold())
+                }
+                (Some(repo), None) => println!("{} ({repo})", service.name.dimmed().bold()),
+                _ => println!("{}", service.name.dimmed().bold()),
+            }
         }
     } else {
         println!("{}", serde_json::to_string_pretty(&body.project)?);
@@ -0,0 +1,178 @@
+use anyhow::bail;
+use clap::Subcommand;
+use is_terminal::IsTerminal;
+
+use crate::{
+    consts::SERVICE_NOT_LINKED,
+    controllers::github::{
+        create_trigger, delete_trigger, get_branches, get_triggers, parse_repo,
+        update_trigger_branch, validate_branch,
+    },
+    util::prompt,
+};
+
+use super::*;
+
+/// Manage the GitHub triggers that deploy the linked service on push
+#[derive(Parser)]
+pub struct Args {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// List the triggers of the linked service
+    List,
+
+    /// Deploy the linked service when a branch of a repository is pushed to
+    Add(AddArgs),
+
+    /// Change the branch a trigger deploys from
+    Update(UpdateArgs),
+
+    /// Remove a trigger, disconnecting auto-deploys
+    Remove(RemoveArgs),
+}
+
+#[derive(Parser)]
+struct AddArgs {
+    /// The GitHub repository, as owner/name
+    #[clap(short, long)]
+    repo: String,
+
+    /// The branch to deploy. Prompts for one if omitted.
+    #[clap(short, long)]
+    branch: Option<String>,
+
+    /// Wait for GitHub check suites to pass before deploying
+    #[clap(long)]
+    check_suites: bool,
+}
+
+#[derive(Parser)]
+struct UpdateArgs {
+    /// The ID of the trigger
+    id: String,
+
+    /// The branch to deploy. Prompts for one if omitted.
+    #[clap(short, long)]
+    branch: Option<String>,
+}
+
+#[derive(Parser)]
+struct RemoveArgs {
+    /// The ID of the trigger
+    id: String,
+
+    /// Skip the confirmation prompt
+    #[clap(short, long)]
+    yes: bool,
+}
+
+pub async fn command(args: Args, json: bool) -> Result<()> {
+    let configs = Configs::new()?;
+    let client = GQLClient::new_authorized(&configs)?;
+    let linked_project = configs.get_linked_project().await?;
+    let Some(ref service) = linked_project.service else {
+        bail!(SERVICE_NOT_LINKED);
+    };
+
+    match args.command {
+        Commands::List => {
+            let triggers = get_triggers(&client, &configs, &linked_project, service).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&triggers)?);
+            } else if triggers.is_empty() {
+                println!("No triggers found");
+            } else {
+                for trigger in triggers {
+                    println!(
+                        "{} {} {}",
+                        trigger.id.dimmed(),
+                        trigger.repository.bold(),
+                        trigger.branch.purple()
+                    );
+                }
+            }
+        }
+        Commands::Add(args) => {
+            let branch = choose_branch(&client, &configs, &args.repo, args.branch).await?;
+            let id = create_trigger(
+                &client,
+                &configs,
+                &linked_project,
+                service,
+                &args.repo,
+                &branch,
+                args.check_suites,
+            )
+            .await?;
+            println!(
+                "Created trigger {} deploying {} on push to {}",
+                id.bold(),
+                args.repo.bold(),
+                branch.purple()
+            );
+        }
+        Commands::Update(args) => {
+            let trigger = get_triggers(&client, &configs, &linked_project, service)
+                .await?
+                .into_iter()
+                .find(|trigger| trigger.id == args.id)
+                .context("Trigger not found")?;
+            let branch = choose_branch(&client, &configs, &trigger.repository, args.branch).await?;
+            update_trigger_branch(&client, &configs, &trigger.id, &branch).await?;
+            println!(
+                "Trigger {} now deploys {} on push to {}",
+                trigger.id.bold(),
+                trigger.repository.bold(),
+                branch.purple()
+            );
+        }
+        Commands::Remove(args) => {
+            let trigger = get_triggers(&client, &configs, &linked_project, service)
+                .await?
+                .into_iter()
+                .find(|trigger| trigger.id == args.id)
+                .context("Trigger not found")?;
+            prompt::confirm(
+                &configs,
+                &format!(
+                    "Remove trigger {} for {}@{}? Pushes will no longer deploy",
+                    trigger.id, trigger.repository, trigger.branch
+                ),
+                args.yes,
+            )?;
+            delete_trigger(&client, &configs, &trigger.id).await?;
+            println!("Removed trigger {}", trigger.id.bold());
+        }
+    }
+    Ok(())
+}
+
+/// Validate `branch` against the branches of `repo`, or let the user pick one
+/// of them if it wasn't given
+pub async fn choose_branch(
+    client: &reqwest::Client,
+    configs: &Configs,
+    repo: &str,
+    branch: Option<String>,
+) -> Result<String> {
+    parse_repo(repo)?;
+    if let Some(branch) = branch {
+        validate_branch(client, configs, repo, &branch).await?;
+        return Ok(branch);
+    }
+    if !std::io::stdout().is_terminal() {
+        bail!("Branch must be provided when not running in a terminal");
+    }
+    let branches = get_branches(client, configs, repo).await?;
+    if branches.is_empty() {
+        bail!("{repo} has no branches");
+    }
+    let branch = inquire::Select::new("Select a branch", branches)
+        .with_render_config(configs.get_render_config())
+        .prompt()?;
+    Ok(branch)
+}
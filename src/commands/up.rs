@@ -1,20 +1,34 @@
 use std::{
-    path::PathBuf,
-    sync::{Arc, Mutex},
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
     time::Duration,
 };
 
+use anyhow::bail;
 use futures::StreamExt;
 use gzp::{deflate::Gzip, ZBuilder};
-use ignore::WalkBuilder;
 use indicatif::{ProgressBar, ProgressFinish, ProgressIterator, ProgressStyle};
 use is_terminal::IsTerminal;
 use synchronized_writer::SynchronizedWriter;
-use tar::Builder;
 
-use crate::{consts::TICK_STRING, entities::UpResponse, subscription::subscribe_graphql};
+use crate::{
+    consts::{SERVICE_NOT_LINKED, TICK_STRING},
+    controllers::deployment::{
+        cancel, get_deployment, get_latest_deployment, get_linked_deployment_ids, is_failure,
+        is_stable, redeploy, wait_for_new_deployment,
+    },
+    entities::UpResponse,
+    subscription::subscribe_graphql,
+    util::{
+        archive, ci,
+        git::GitMetadata,
+        hooks::{self, Hooks},
+        secrets,
+    },
+};
 
-use super::*;
+use super::{queries::deployments::DeploymentStatus, *};
 
 /// Upload and deploy project from the current directory
 #[derive(Parser)]
@@ -24,69 +38,363 @@ pub struct Args {
     #[clap(short, long)]
     /// Don't attach to the log stream
     detach: bool,
+
+    #[clap(short, long)]
+    /// Upload even if nothing has changed since the last deploy
+    force: bool,
+
+    #[clap(long, conflicts_with = "force")]
+    /// Redeploy the latest deployment if nothing has changed since the last deploy
+    redeploy: bool,
+
+    #[clap(short, long)]
+    /// Message to attach to the deployment
+    message: Option<String>,
+
+    #[clap(long)]
+    /// Upload the contents of symlinks instead of the links themselves
+    follow_symlinks: bool,
+
+    #[clap(long)]
+    /// Upload even if files look like they contain secrets
+    allow_secrets: bool,
+
+    #[clap(short, long, conflicts_with_all = ["detach", "force", "redeploy"])]
+    /// Redeploy whenever files change, cancelling deployments that are still in progress
+    watch: bool,
+
+    #[clap(long, requires = "path")]
+    /// Upload this directory instead, and deploy `path` inside of it as the service's root directory
+    root: Option<PathBuf>,
 }
 
+/// How often to check for changes with `--watch`
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+/// How long files must stay unchanged before redeploying with `--watch`
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+/// How many walked entries may wait to be compressed or scanned for secrets
+const ENTRY_BUFFER: usize = 1024;
+
 pub async fn command(args: Args, _json: bool) -> Result<()> {
-    let configs = Configs::new()?;
-    let hostname = configs.get_host();
+    let mut configs = Configs::new()?;
     let client = GQLClient::new_authorized(&configs)?;
     let linked_project = configs.get_linked_project().await?;
-    let spinner = if !std::io::stdout().is_terminal() {
-        let spinner = ProgressBar::new_spinner()
+    let (prefix, root_directory) = match args.root {
+        Some(ref root) => {
+            let package = args.path.as_deref().unwrap_or(Path::new("."));
+            let root_directory = resolve_root_directory(&linked_project, root, package)?;
+            (root.clone(), Some(root_directory))
+        }
+        None => (args.path.clone().unwrap_or_else(|| ".".into()), None),
+    };
+
+    let hooks = Hooks::from_path(&prefix)?;
+
+    if args.watch {
+        return watch(
+            &args,
+            &client,
+            &configs,
+            &linked_project,
+            &prefix,
+            &hooks,
+            root_directory.as_ref(),
+        )
+        .await;
+    }
+
+    hooks::run(&hooks.pre_up, &prefix, &[]).await?;
+    let (body, hash) = build_archive(&args, &prefix)?;
+    // The same tree deployed from another directory of it is a different deploy
+    let up_hash = match root_directory {
+        Some(ref root_directory) => format!("{hash}:{}", root_directory.path),
+        None => hash.clone(),
+    };
+
+    if !args.force && linked_project.get_last_up_hash() == Some(&up_hash) {
+        println!("No changes since last deploy");
+        if args.redeploy {
+            let latest_deployment =
+                get_latest_deployment(&client, &configs, &linked_project).await?;
+            let deployment_id = redeploy(&client, &configs, &latest_deployment.id).await?;
+            println!("Redeploying as {}", deployment_id.bold());
+        } else {
+            println!("Use {} to upload anyway", "--force".bold());
+        }
+        return Ok(());
+    }
+
+    if let Some(ref root_directory) = root_directory {
+        set_root_directory(&client, &configs, root_directory).await?;
+    }
+    let (response, git) = upload(
+        &args,
+        &client,
+        &configs,
+        &linked_project,
+        &prefix,
+        body,
+        &hash,
+    )
+    .await?;
+    // Until this upload is known to have deployed, the last hash no longer describes
+    // what is running
+    configs.set_last_up_hash(&linked_project, None);
+    configs.write()?;
+    let Some(deployment_id) = response.deployment_id.clone().filter(|_| !args.detach) else {
+        write_summary(&response, git.as_ref(), None)?;
+        if !hooks.post_up.is_empty() {
+            println!(
+                "{}",
+                "Skipping post_up hooks since the deployment isn't being followed".dimmed()
+            );
+        }
+        return Ok(());
+    };
+
+    let status = follow(
+        &client,
+        &configs,
+        &linked_project.project,
+        deployment_id.clone(),
+        true,
+    )
+    .await;
+    if matches!(status, Ok(Some(DeploymentStatus::SUCCESS))) {
+        configs.set_last_up_hash(&linked_project, Some(up_hash));
+        configs.write()?;
+    }
+    match status {
+        Ok(Some(ref status)) if is_failure(status) => {
+            ci::error(&format!("Deployment {deployment_id} {status:?}"));
+        }
+        Err(ref e) => ci::error(&e.to_string()),
+        _ => {}
+    }
+    let status = status?;
+    write_summary(&response, git.as_ref(), status.as_ref())?;
+    if matches!(status, Some(DeploymentStatus::SUCCESS)) {
+        let envs = [
+            ("RAILWAY_DEPLOYMENT_ID", deployment_id.as_str()),
+            ("RAILWAY_DEPLOYMENT_URL", response.url.as_str()),
+            (
+                "RAILWAY_DEPLOYMENT_DOMAIN",
+                response.deployment_domain.as_str(),
+            ),
+        ];
+        hooks::run(&hooks.post_up, &prefix, &envs).await?;
+    }
+    Ok(())
+}
+
+/// Whether to show spinners and progress bars, which only make sense in an interactive terminal
+fn show_progress() -> bool {
+    std::io::stdout().is_terminal() && !ci::is_ci()
+}
+
+/// Add a markdown summary of the deployment to the GitHub Actions step summary.
+/// `git` is the metadata sent with the upload, and `status` is `None` when the
+/// deployment wasn't followed.
+fn write_summary(
+    response: &UpResponse,
+    git: Option<&GitMetadata>,
+    status: Option<&DeploymentStatus>,
+) -> Result<()> {
+    let mut rows = vec![];
+    if let Some(ref id) = response.deployment_id {
+        rows.push(("Deployment", format!("`{id}`")));
+    }
+    let status = match status {
+        Some(status) => format!("{status:?}"),
+        None => "Uploaded".to_owned(),
+    };
+    rows.push(("Status", status));
+    if let Some(git) = git {
+        let mut commit = format!("`{}`", git.short_hash());
+        if let Some(subject) = git.commit_message.lines().next() {
+            commit.push(' ');
+            commit.push_str(subject);
+        }
+        rows.push(("Commit", commit));
+    }
+    rows.push(("Build Logs", format!("[View logs]({})", response.logs_url)));
+
+    let mut markdown = "### Railway deployment\n\n| | |\n| --- | --- |\n".to_owned();
+    for (name, value) in rows {
+        markdown.push_str(&format!("| {name} | {} |\n", value.replace('|', "\\|")));
+    }
+    ci::write_summary(&markdown)
+}
+
+/// The root directory of the linked service to deploy from, set with `--root`
+struct RootDirectory {
+    service_id: String,
+    /// Path of the package inside the upload, starting with `/`
+    path: String,
+}
+
+/// Get the root directory to deploy `package` from, relative to the uploaded `root`
+fn resolve_root_directory(
+    linked_project: &RailwayProject,
+    root: &Path,
+    package: &Path,
+) -> Result<RootDirectory> {
+    let Some(ref service_id) = linked_project.service else {
+        bail!("{SERVICE_NOT_LINKED} before using --root");
+    };
+    let canonical_root = root
+        .canonicalize()
+        .with_context(|| format!("Unable to read {}", root.display()))?;
+    let canonical_package = package
+        .canonicalize()
+        .with_context(|| format!("Unable to read {}", package.display()))?;
+    let Ok(relative) = canonical_package.strip_prefix(&canonical_root) else {
+        bail!("{} is not inside of {}", package.display(), root.display());
+    };
+    let path = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Ok(RootDirectory {
+        service_id: service_id.clone(),
+        path: format!("/{path}"),
+    })
+}
+
+/// Point the linked service's root directory at `root_directory`
+async fn set_root_directory(
+    client: &reqwest::Client,
+    configs: &Configs,
+    root_directory: &RootDirectory,
+) -> Result<()> {
+    let vars = mutations::service_instance_root_directory_update::Variables {
+        service_id: root_directory.service_id.clone(),
+        root_directory: root_directory.path.clone(),
+    };
+    post_graphql::<mutations::ServiceInstanceRootDirectoryUpdate, _>(
+        client,
+        configs.get_backboard(),
+        vars,
+    )
+    .await?;
+    println!(
+        "  {}: {}",
+        "Root Directory".green().bold(),
+        root_directory.path
+    );
+    Ok(())
+}
+
+/// Index, scan and compress the files under `prefix`.
+/// Returns the compressed archive and its content hash.
+fn build_archive(args: &Args, prefix: &Path) -> Result<(Vec<u8>, String)> {
+    let progress = if show_progress() {
+        let pg = ProgressBar::new_spinner()
             .with_style(
                 ProgressStyle::default_spinner()
                     .tick_chars(TICK_STRING)
-                    .template("{spinner:.green} {msg:.cyan.bold}")?,
+                    .template("{spinner:.green} {msg:.cyan.bold} {pos} files")?,
             )
-            .with_message("Indexing".to_string());
-        spinner.enable_steady_tick(Duration::from_millis(100));
-        Some(spinner)
+            .with_message("Compressing")
+            .with_finish(ProgressFinish::WithMessage("Compressed".into()));
+        pg.enable_steady_tick(Duration::from_millis(100));
+        pg
     } else {
-        println!("Indexing...");
-        None
+        println!("Compressing...");
+        ProgressBar::hidden()
     };
+
     let bytes = Vec::<u8>::new();
     let arc = Arc::new(Mutex::new(bytes));
     let mut parz = ZBuilder::<Gzip, _>::new()
         .num_threads(num_cpus::get())
         .from_writer(SynchronizedWriter::new(arc.clone()));
-    {
-        let mut archive = Builder::new(&mut parz);
-        let mut builder = WalkBuilder::new(args.path.unwrap_or_else(|| ".".into()));
-        builder.add_custom_ignore_filename(".railwayignore");
-        let walker = builder.follow_links(true).hidden(false);
-        let walked = walker.build().collect::<Vec<_>>();
-        if let Some(spinner) = spinner {
-            spinner.finish_with_message("Indexed");
-        }
-        if !std::io::stdout().is_terminal() {
-            let pg = ProgressBar::new(walked.len() as u64)
-                .with_style(
-                    ProgressStyle::default_bar()
-                        .template("{spinner:.green} {msg:.cyan.bold} [{bar:20}] {percent}% ")?
-                        .progress_chars("=> ")
-                        .tick_chars(TICK_STRING),
-                )
-                .with_message("Compressing")
-                .with_finish(ProgressFinish::WithMessage("Compressed".into()));
-            pg.enable_steady_tick(Duration::from_millis(100));
 
-            for entry in walked.into_iter().progress_with(pg) {
-                archive.append_path(entry?.path())?;
-            }
-        } else {
-            for entry in walked.into_iter() {
-                archive.append_path(entry?.path())?;
+    // The walk yields entries in archive order, so they are compressed and scanned for
+    // secrets while the walk is still going rather than once it finishes
+    let follow_symlinks = args.follow_symlinks;
+    let entries = archive::walk(prefix, follow_symlinks)?;
+    let (archive_tx, archive_rx) = mpsc::sync_channel(ENTRY_BUFFER);
+    let (scan_tx, scan_rx) = mpsc::sync_channel(ENTRY_BUFFER);
+    let scan_tx = (!args.allow_secrets).then_some(scan_tx);
+    let (hash, findings) = std::thread::scope(|scope| {
+        scope.spawn(move || {
+            for entry in entries {
+                if let (Ok(entry), Some(scan_tx)) = (&entry, &scan_tx) {
+                    // The scanner only stops early on an error, which is reported below
+                    let _ = scan_tx.send(entry.clone());
+                }
+                if archive_tx.send(entry).is_err() {
+                    break;
+                }
             }
+        });
+        let scanner = scope.spawn(move || secrets::scan(prefix, scan_rx, follow_symlinks));
+        let hash = archive::write(
+            &mut parz,
+            archive_rx.into_iter().progress_with(progress),
+            follow_symlinks,
+        );
+        let findings = scanner.join().expect("secret scanner panicked");
+        (hash, findings)
+    });
+    let hash = hash?;
+    parz.finish()?;
+
+    let findings = findings?;
+    if !findings.is_empty() {
+        eprintln!(
+            "{}",
+            "Found files that look like they contain secrets:"
+                .red()
+                .bold()
+        );
+        for finding in findings {
+            eprintln!("  {finding}");
         }
+        bail!(
+            "Refusing to upload secrets. Add the files to .railwayignore, allow them with `{}<pattern>` in .railwayignore, or pass --allow-secrets",
+            secrets::ALLOW_SECRET_PREFIX
+        );
     }
-    parz.finish()?;
 
-    let builder = client.post(format!(
+    let body = arc.lock().unwrap().clone();
+    Ok((body, hash))
+}
+
+/// Upload an archive built by `build_archive`, along with the git metadata of `prefix`
+/// which is returned next to the response.
+/// Unless `--detach` was given, the id of the created deployment is filled in if it can be found.
+async fn upload(
+    args: &Args,
+    client: &reqwest::Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+    prefix: &Path,
+    body: Vec<u8>,
+    hash: &str,
+) -> Result<(UpResponse, Option<GitMetadata>)> {
+    let hostname = configs.get_host();
+    let git = GitMetadata::from_path(prefix);
+    let mut builder = client.post(format!(
         "https://backboard.{hostname}/project/{}/environment/{}/up",
         linked_project.project, linked_project.environment
     ));
-    let spinner = if !std::io::stdout().is_terminal() {
+    if let Some(ref git) = git {
+        builder = builder.query(git);
+    }
+    if let Some(ref message) = args.message {
+        builder = builder.query(&[("message", message)]);
+    }
+    // In case the response doesn't say which deployment the upload created, remember
+    // the existing ones to tell it apart
+    let known = if args.detach {
+        HashSet::new()
+    } else {
+        get_linked_deployment_ids(client, configs, linked_project).await?
+    };
+    let spinner = if show_progress() {
         let spinner = ProgressBar::new_spinner()
             .with_style(
                 ProgressStyle::default_spinner()
@@ -101,7 +409,6 @@ pub async fn command(args: Args, _json: bool) -> Result<()> {
         None
     };
 
-    let body = arc.lock().unwrap().clone();
     let res = builder
         .header("Content-Type", "multipart/form-data")
         .body(body)
@@ -109,47 +416,347 @@ pub async fn command(args: Args, _json: bool) -> Result<()> {
         .await?
         .error_for_status()?;
 
-    let body = res.json::<UpResponse>().await?;
+    let mut body = res.json::<UpResponse>().await?;
     if let Some(spinner) = spinner {
         spinner.finish_with_message("Uploaded");
     }
+    if let Some(ref git) = git {
+        let mut details = vec![];
+        if let Some(branch) = git.branch.as_deref() {
+            details.push(branch);
+        }
+        if git.dirty {
+            details.push("dirty");
+        }
+        let details = if details.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", details.join(", "))
+        };
+        println!(
+            "  {}: {}{} {}",
+            "Commit".green().bold(),
+            git.short_hash(),
+            details,
+            git.commit_message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .dimmed()
+        );
+    }
+    println!("  {}: {}", "Content Hash".green().bold(), hash);
     println!("  {}: {}", "Build Logs".green().bold(), body.logs_url);
-    if args.detach {
-        return Ok(());
+    if !args.detach && body.deployment_id.is_none() {
+        body.deployment_id =
+            find_uploaded_deployment(client, configs, linked_project, &known).await;
+        if body.deployment_id.is_none() {
+            println!(
+                "Unable to find the deployment for this upload, follow it at {}",
+                body.logs_url
+            );
+        }
     }
+    Ok((body, git))
+}
 
-    let vars = queries::deployments::Variables {
-        project_id: linked_project.project.clone(),
+/// Stream the build logs of a deployment, then its deploy logs.
+/// With `until_stable`, stops once the deployment reaches a stable state and returns it,
+/// otherwise keeps streaming until the log stream ends.
+/// Returns `None` if interrupted with Ctrl-C.
+async fn follow(
+    client: &reqwest::Client,
+    configs: &Configs,
+    project_id: &str,
+    deployment_id: String,
+    until_stable: bool,
+) -> Result<Option<DeploymentStatus>> {
+    let vars = subscriptions::build_logs::Variables {
+        deployment_id: deployment_id.clone(),
+        filter: Some(String::new()),
+        limit: Some(500),
     };
 
-    let res =
-        post_graphql::<queries::Deployments, _>(&client, configs.get_backboard(), vars).await?;
-
-    let body = res.data.context("Failed to retrieve response body")?;
-
-    let mut deployments: Vec<_> = body
-        .project
-        .deployments
-        .edges
-        .into_iter()
-        .map(|deployment| deployment.node)
-        .collect();
-    deployments.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-    let latest_deployment = deployments.first().context("No deployments found")?;
-    if !std::io::stdout().is_terminal() {
-        let vars = subscriptions::build_logs::Variables {
-            deployment_id: latest_deployment.id.clone(),
-            filter: Some(String::new()),
-            limit: Some(500),
-        };
+    let (_client, mut log_stream) = subscribe_graphql::<subscriptions::BuildLogs>(vars).await?;
+    ci::start_group("Build Logs");
+    loop {
+        tokio::select! {
+            log = log_stream.next() => {
+                let Some(Ok(log)) = log else {
+                    break;
+                };
+                let log = log.data.context("Failed to retrieve log")?;
+                for line in log.build_logs {
+                    println!("{}", line.message);
+                }
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(None),
+        }
+    }
+
+    ci::end_group();
+
+    let status = get_deployment(client, configs, project_id, &deployment_id)
+        .await?
+        .status;
+    if matches!(status, DeploymentStatus::FAILED) {
+        bail!("Build failed");
+    }
+
+    if ci::is_github_actions() {
+        ci::start_group("Deploy Logs");
+    } else {
+        println!(
+            "{}",
+            "==================== Deploy Logs ====================".dimmed()
+        );
+    }
+
+    let vars = subscriptions::deployment_logs::Variables {
+        deployment_id: deployment_id.clone(),
+        filter: Some(String::new()),
+        limit: Some(500),
+    };
+
+    let (_client, mut log_stream) =
+        subscribe_graphql::<subscriptions::DeploymentLogs>(vars).await?;
+    let mut interval = tokio::time::interval(Duration::from_secs(2));
+    loop {
+        tokio::select! {
+            log = log_stream.next() => {
+                let Some(Ok(log)) = log else {
+                    break;
+                };
+                let log = log.data.context("Failed to retrieve log")?;
+                for line in log.deployment_logs {
+                    println!("{}", line.message);
+                }
+            }
+            _ = interval.tick(), if until_stable => {
+                let status = get_deployment(client, configs, project_id, &deployment_id)
+                    .await?
+                    .status;
+                if is_stable(&status) {
+                    ci::end_group();
+                    println!("{} {:?}", "Deployment".bold(), status);
+                    return Ok(Some(status));
+                }
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(None),
+        }
+    }
+    ci::end_group();
+
+    let status = get_deployment(client, configs, project_id, &deployment_id)
+        .await?
+        .status;
+    Ok(Some(status))
+}
+
+/// Deploy, then redeploy whenever the files that would be uploaded change.
+/// A deployment that is still in progress when a newer change arrives is cancelled.
+/// `pre_up` hooks run before every deploy, `post_up` hooks don't run.
+async fn watch(
+    args: &Args,
+    client: &reqwest::Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+    prefix: &Path,
+    hooks: &Hooks,
+    root_directory: Option<&RootDirectory>,
+) -> Result<()> {
+    let (mut deployment_id, mut fingerprint) = deploy(
+        args,
+        client,
+        configs,
+        linked_project,
+        prefix,
+        hooks,
+        root_directory,
+    )
+    .await;
+    let mut following = Box::pin(follow_or_wait(
+        client,
+        configs,
+        &linked_project.project,
+        deployment_id.clone(),
+    ));
+    let mut changes = Box::pin(wait_for_changes(
+        prefix.to_path_buf(),
+        args.follow_symlinks,
+        fingerprint,
+    ));
+    let mut followed = false;
 
-        let (_client, mut log_stream) = subscribe_graphql::<subscriptions::BuildLogs>(vars).await?;
-        while let Some(Ok(log)) = log_stream.next().await {
-            let log = log.data.context("Failed to retrieve log")?;
-            for line in log.build_logs {
-                println!("{}", line.message);
+    loop {
+        tokio::select! {
+            result = &mut following, if !followed => {
+                followed = true;
+                if let Err(e) = result {
+                    eprintln!("{}", e.to_string().red());
+                }
+                println!("{}", "Watching for changes...".dimmed());
             }
+            _ = &mut changes => {
+                println!("{}", "Change detected, redeploying".yellow().bold());
+
+                if let Some(ref id) = deployment_id {
+                    if let Err(e) =
+                        cancel_if_running(client, configs, &linked_project.project, id).await
+                    {
+                        eprintln!("{}", e.to_string().red());
+                    }
+                }
+
+                (deployment_id, fingerprint) = deploy(
+                    args,
+                    client,
+                    configs,
+                    linked_project,
+                    prefix,
+                    hooks,
+                    root_directory,
+                )
+                .await;
+                following = Box::pin(follow_or_wait(
+                    client,
+                    configs,
+                    &linked_project.project,
+                    deployment_id.clone(),
+                ));
+                followed = false;
+                changes = Box::pin(wait_for_changes(
+                    prefix.to_path_buf(),
+                    args.follow_symlinks,
+                    fingerprint,
+                ));
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    Ok(())
+}
+
+/// Build and upload the project, printing errors instead of returning them so that
+/// watching can continue after a failed deploy.
+/// Returns the id of the deployment and the fingerprint of the files it was built from.
+async fn deploy(
+    args: &Args,
+    client: &reqwest::Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+    prefix: &Path,
+    hooks: &Hooks,
+    root_directory: Option<&RootDirectory>,
+) -> (Option<String>, Option<u64>) {
+    let mut fingerprint = None;
+    let result = async {
+        hooks::run(&hooks.pre_up, prefix, &[]).await?;
+        // Taken after the hooks so that files they write don't trigger another deploy,
+        // but before archiving so that edits made while uploading do
+        fingerprint = try_fingerprint(prefix, args.follow_symlinks);
+        let (body, hash) = build_archive(args, prefix)?;
+        if let Some(root_directory) = root_directory {
+            set_root_directory(client, configs, root_directory).await?;
         }
+        upload(args, client, configs, linked_project, prefix, body, &hash).await
+    }
+    .await;
+    let deployment_id = match result {
+        Ok((response, _)) => response.deployment_id,
+        Err(e) => {
+            eprintln!("{}", e.to_string().red());
+            None
+        }
+    };
+    (deployment_id, fingerprint)
+}
+
+async fn follow_or_wait(
+    client: &reqwest::Client,
+    configs: &Configs,
+    project_id: &str,
+    deployment_id: Option<String>,
+) -> Result<Option<DeploymentStatus>> {
+    match deployment_id {
+        Some(deployment_id) => follow(client, configs, project_id, deployment_id, false).await,
+        None => Ok(None),
+    }
+}
+
+/// Cancel `deployment_id` if it is still building or deploying
+async fn cancel_if_running(
+    client: &reqwest::Client,
+    configs: &Configs,
+    project_id: &str,
+    deployment_id: &str,
+) -> Result<()> {
+    let status = get_deployment(client, configs, project_id, deployment_id)
+        .await?
+        .status;
+    if !is_stable(&status) {
+        cancel(client, configs, deployment_id).await?;
+        println!("Cancelled deployment {}", deployment_id.bold());
     }
     Ok(())
 }
+
+/// Fingerprint the files that would be uploaded, printing errors instead of returning
+/// them so that a file that briefly can't be read doesn't end the watch
+fn try_fingerprint(prefix: &Path, follow_symlinks: bool) -> Option<u64> {
+    match archive::fingerprint(prefix, follow_symlinks) {
+        Ok(fingerprint) => Some(fingerprint),
+        Err(e) => {
+            eprintln!("{}", e.to_string().red());
+            None
+        }
+    }
+}
+
+/// Poll the files that would be uploaded until they change, then wait for them to
+/// settle so that a burst of changes only triggers a single deploy. Polls that fail
+/// are skipped, and without a `last` fingerprint the first successful poll is used.
+async fn wait_for_changes(prefix: PathBuf, follow_symlinks: bool, mut last: Option<u64>) {
+    let fingerprint = |prefix: PathBuf| async move {
+        tokio::task::spawn_blocking(move || try_fingerprint(&prefix, follow_symlinks))
+            .await
+            .ok()
+            .flatten()
+    };
+
+    while last.is_none() {
+        tokio::time::sleep(WATCH_INTERVAL).await;
+        last = fingerprint(prefix.clone()).await;
+    }
+    let mut current = last;
+    while current.is_none() || current == last {
+        tokio::time::sleep(WATCH_INTERVAL).await;
+        current = fingerprint(prefix.clone()).await;
+    }
+    loop {
+        tokio::time::sleep(WATCH_DEBOUNCE).await;
+        let next = fingerprint(prefix.clone()).await;
+        if next.is_none() || next == current {
+            return;
+        }
+        current = next;
+    }
+}
+
+/// Find the deployment created by our upload when the server response does not include its id.
+/// `known` are the ids of the deployments that existed before uploading.
+/// The upload already succeeded, so a failed lookup is printed rather than returned.
+async fn find_uploaded_deployment(
+    client: &reqwest::Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+    known: &HashSet<String>,
+) -> Option<String> {
+    match wait_for_new_deployment(client, configs, linked_project, known).await {
+        Ok(deployment) => deployment.map(|deployment| deployment.id),
+        Err(e) => {
+            eprintln!("{}", e.to_string().red());
+            None
+        }
+    }
+}
@@ -24,6 +24,23 @@ pub struct RailwayProject {
     pub environment: String,
     pub environment_name: Option<String>,
     pub service: Option<String>,
+    /// Content hash of the last upload that deployed successfully, keyed by environment and service
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_up_hashes: BTreeMap<String, String>,
+}
+
+impl RailwayProject {
+    fn up_hash_key(&self) -> String {
+        format!(
+            "{}:{}",
+            self.environment,
+            self.service.as_deref().unwrap_or_default()
+        )
+    }
+
+    pub fn get_last_up_hash(&self) -> Option<&String> {
+        self.last_up_hashes.get(&self.up_hash_key())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -183,6 +200,7 @@ impl Configs {
                 environment: data.project_token.environment.id,
                 environment_name: Some(data.project_token.environment.name),
                 service: None,
+                last_up_hashes: BTreeMap::new(),
             };
             return Ok(project);
         }
@@ -220,6 +238,7 @@ impl Configs {
             environment: environment_id,
             environment_name,
             service: None,
+            last_up_hashes: BTreeMap::new(),
         };
         self.root_config.projects.insert(path, project);
         Ok(())
@@ -241,6 +260,19 @@ impl Configs {
         Ok(project)
     }
 
+    /// Record the content hash of the last upload for the environment and service of `project`.
+    /// Does nothing when the project isn't linked to a directory, e.g. when using a project token.
+    pub fn set_last_up_hash(&mut self, project: &RailwayProject, hash: Option<String>) {
+        let Some(linked_project) = self.root_config.projects.get_mut(&project.project_path) else {
+            return;
+        };
+        let key = project.up_hash_key();
+        match hash {
+            Some(hash) => linked_project.last_up_hashes.insert(key, hash),
+            None => linked_project.last_up_hashes.remove(&key),
+        };
+    }
+
     pub fn unlink_service(&mut self) -> Result<()> {
         let linked_project = self.get_linked_project_mut()?;
         linked_project.service = None;
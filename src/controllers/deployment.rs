@@ -0,0 +1,296 @@
+use std::{collections::HashSet, time::Duration};
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+
+use crate::{
+    client::post_graphql,
+    config::{Configs, RailwayProject},
+    gql::{
+        mutations,
+        queries::{
+            self,
+            deployments::{DeploymentStatus, DeploymentsProjectDeploymentsEdgesNode},
+        },
+    },
+};
+
+pub type Deployment = DeploymentsProjectDeploymentsEdgesNode;
+
+/// How often to check the status of a deployment that is being waited on
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long a restarted deployment may keep its previous status before we assume the
+/// restart finished between two polls
+const RESTART_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Get all deployments of a project, newest first
+pub async fn get_deployments(
+    client: &Client,
+    configs: &Configs,
+    project_id: &str,
+) -> Result<Vec<Deployment>> {
+    let vars = queries::deployments::Variables {
+        project_id: project_id.to_owned(),
+    };
+
+    let res =
+        post_graphql::<queries::Deployments, _>(client, configs.get_backboard(), vars).await?;
+
+    let body = res.data.context("Failed to retrieve response body")?;
+
+    let mut deployments: Vec<_> = body
+        .project
+        .deployments
+        .edges
+        .into_iter()
+        .map(|deployment| deployment.node)
+        .collect();
+    deployments.sort_by_key(|d| std::cmp::Reverse(d.created_at));
+    Ok(deployments)
+}
+
+/// Get the deployments belonging to the linked environment and service, newest first
+pub async fn get_linked_deployments(
+    client: &Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+) -> Result<Vec<Deployment>> {
+    let deployments = get_deployments(client, configs, &linked_project.project).await?;
+    Ok(deployments
+        .into_iter()
+        .filter(|deployment| is_linked(deployment, linked_project))
+        .collect())
+}
+
+/// Get the ids of the deployments belonging to the linked environment and service.
+/// Taken before an upload or rollback, they tell which deployment it created without
+/// having to compare our clock with the server's.
+pub async fn get_linked_deployment_ids(
+    client: &Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+) -> Result<HashSet<String>> {
+    let deployments = get_linked_deployments(client, configs, linked_project).await?;
+    Ok(deployments
+        .into_iter()
+        .map(|deployment| deployment.id)
+        .collect())
+}
+
+/// Find the oldest deployment of the linked environment and service that isn't in `known`
+pub async fn find_new_deployment(
+    client: &Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+    known: &HashSet<String>,
+) -> Result<Option<Deployment>> {
+    let deployments = get_linked_deployments(client, configs, linked_project).await?;
+    Ok(deployments
+        .into_iter()
+        .rev()
+        .find(|deployment| !known.contains(&deployment.id)))
+}
+
+/// Like `find_new_deployment`, but retries for a short while since it can take
+/// a moment for a new deployment to show up
+pub async fn wait_for_new_deployment(
+    client: &Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+    known: &HashSet<String>,
+) -> Result<Option<Deployment>> {
+    for _ in 0..10 {
+        if let Some(deployment) =
+            find_new_deployment(client, configs, linked_project, known).await?
+        {
+            return Ok(Some(deployment));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    Ok(None)
+}
+
+pub async fn get_latest_deployment(
+    client: &Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+) -> Result<Deployment> {
+    get_linked_deployments(client, configs, linked_project)
+        .await?
+        .into_iter()
+        .next()
+        .context("No deployments found")
+}
+
+pub async fn get_deployment(
+    client: &Client,
+    configs: &Configs,
+    project_id: &str,
+    deployment_id: &str,
+) -> Result<Deployment> {
+    get_deployments(client, configs, project_id)
+        .await?
+        .into_iter()
+        .find(|deployment| deployment.id == deployment_id)
+        .context("Deployment not found")
+}
+
+/// Download the gzipped tarball of the source a deployment was built from
+pub async fn get_snapshot(
+    client: &Client,
+    configs: &Configs,
+    deployment_id: &str,
+) -> Result<Vec<u8>> {
+    let vars = queries::deployment_snapshot_code_uri::Variables {
+        deployment_id: deployment_id.to_owned(),
+    };
+    let res = post_graphql::<queries::DeploymentSnapshotCodeUri, _>(
+        client,
+        configs.get_backboard(),
+        vars,
+    )
+    .await?;
+    let uri = res
+        .data
+        .context("Failed to retrieve snapshot")?
+        .deployment_snapshot_code_uri;
+
+    // The snapshot is served from storage, which must not receive our credentials
+    let bytes = Client::new()
+        .get(uri)
+        .send()
+        .await?
+        .error_for_status()
+        .context("Failed to download snapshot")?
+        .bytes()
+        .await?;
+    Ok(bytes.to_vec())
+}
+
+/// Roll back to a previous deployment
+pub async fn rollback(client: &Client, configs: &Configs, deployment_id: &str) -> Result<()> {
+    let vars = mutations::deployment_rollback::Variables {
+        id: deployment_id.to_owned(),
+    };
+    post_graphql::<mutations::DeploymentRollback, _>(client, configs.get_backboard(), vars).await?;
+    Ok(())
+}
+
+/// Redeploy a deployment, returning the ID of the new deployment
+pub async fn redeploy(client: &Client, configs: &Configs, deployment_id: &str) -> Result<String> {
+    let vars = mutations::deployment_redeploy::Variables {
+        id: deployment_id.to_owned(),
+    };
+    let res =
+        post_graphql::<mutations::DeploymentRedeploy, _>(client, configs.get_backboard(), vars)
+            .await?;
+    let body = res.data.context("Failed to redeploy")?;
+    Ok(body.deployment_redeploy.id)
+}
+
+/// Restart the running containers of a deployment without rebuilding it
+pub async fn restart(client: &Client, configs: &Configs, deployment_id: &str) -> Result<()> {
+    let vars = mutations::deployment_restart::Variables {
+        id: deployment_id.to_owned(),
+    };
+    post_graphql::<mutations::DeploymentRestart, _>(client, configs.get_backboard(), vars).await?;
+    Ok(())
+}
+
+/// Cancel a deployment that is still building or deploying
+pub async fn cancel(client: &Client, configs: &Configs, deployment_id: &str) -> Result<()> {
+    let vars = mutations::deployment_cancel::Variables {
+        id: deployment_id.to_owned(),
+    };
+    post_graphql::<mutations::DeploymentCancel, _>(client, configs.get_backboard(), vars).await?;
+    Ok(())
+}
+
+/// Remove a deployment, taking it offline
+pub async fn remove(client: &Client, configs: &Configs, deployment_id: &str) -> Result<()> {
+    let vars = mutations::deployment_remove::Variables {
+        id: deployment_id.to_owned(),
+    };
+    post_graphql::<mutations::DeploymentRemove, _>(client, configs.get_backboard(), vars).await?;
+    Ok(())
+}
+
+/// Poll a deployment until it reaches a stable status, calling `on_change` with
+/// its initial status and every time it changes
+pub async fn wait_for_deployment(
+    client: &Client,
+    configs: &Configs,
+    project_id: &str,
+    deployment_id: &str,
+    mut on_change: impl FnMut(&DeploymentStatus),
+) -> Result<DeploymentStatus> {
+    let mut last = None;
+    loop {
+        let status = get_deployment(client, configs, project_id, deployment_id)
+            .await?
+            .status;
+        if last != Some(std::mem::discriminant(&status)) {
+            last = Some(std::mem::discriminant(&status));
+            on_change(&status);
+        }
+        if is_stable(&status) {
+            return Ok(status);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Wait for a restarted deployment to settle again. The deployment keeps its previous
+/// status until the restart kicks in, so first wait for the status to change from
+/// `previous` (giving up after [`RESTART_TIMEOUT`]) and only then for it to be stable.
+pub async fn wait_for_restart(
+    client: &Client,
+    configs: &Configs,
+    project_id: &str,
+    deployment_id: &str,
+    previous: &DeploymentStatus,
+    on_change: impl FnMut(&DeploymentStatus),
+) -> Result<DeploymentStatus> {
+    let deadline = tokio::time::Instant::now() + RESTART_TIMEOUT;
+    loop {
+        let status = get_deployment(client, configs, project_id, deployment_id)
+            .await?
+            .status;
+        if std::mem::discriminant(&status) != std::mem::discriminant(previous) {
+            break;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Ok(status);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    wait_for_deployment(client, configs, project_id, deployment_id, on_change).await
+}
+
+/// A deployment is stable once it will no longer transition on its own
+pub fn is_stable(status: &DeploymentStatus) -> bool {
+    matches!(
+        status,
+        DeploymentStatus::SUCCESS
+            | DeploymentStatus::FAILED
+            | DeploymentStatus::CRASHED
+            | DeploymentStatus::REMOVED
+            | DeploymentStatus::SKIPPED
+    )
+}
+
+/// Whether a deployment ended without running successfully
+pub fn is_failure(status: &DeploymentStatus) -> bool {
+    matches!(status, DeploymentStatus::FAILED | DeploymentStatus::CRASHED)
+}
+
+fn is_linked(deployment: &Deployment, linked_project: &RailwayProject) -> bool {
+    if deployment.environment_id != linked_project.environment {
+        return false;
+    }
+    match linked_project.service {
+        Some(ref service) => deployment.service_id.as_ref() == Some(service),
+        None => true,
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{bail, Context, Result};
+use reqwest::Client;
+
+use crate::{
+    client::post_graphql,
+    config::{Configs, RailwayProject},
+    gql::{mutations, queries},
+};
+
+pub type Trigger = queries::deployment_triggers::DeploymentTriggersDeploymentTriggersEdgesNode;
+
+/// Split a repository in the `owner/name` form
+pub fn parse_repo(repo: &str) -> Result<(&str, &str)> {
+    match repo.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+            Ok((owner, name))
+        }
+        _ => bail!("Invalid repository {repo}, expected the form owner/name"),
+    }
+}
+
+/// Get the names of the branches of a GitHub repository in the `owner/name` form.
+/// Fails if the repository doesn't exist or Railway has no access to it.
+pub async fn get_branches(client: &Client, configs: &Configs, repo: &str) -> Result<Vec<String>> {
+    let (owner, name) = parse_repo(repo)?;
+    let vars = queries::git_hub_repo_branches::Variables {
+        owner: owner.to_owned(),
+        repo: name.to_owned(),
+    };
+    let res = post_graphql::<queries::GitHubRepoBranches, _>(client, configs.get_backboard(), vars)
+        .await
+        .with_context(|| {
+            format!("Unable to access {repo}. Make sure the Railway GitHub app is installed for it")
+        })?;
+    let body = res.data.context("Failed to retrieve branches")?;
+    Ok(body
+        .github_repo_branches
+        .into_iter()
+        .map(|branch| branch.name)
+        .collect())
+}
+
+/// Make sure `branch` exists in `repo`
+pub async fn validate_branch(
+    client: &Client,
+    configs: &Configs,
+    repo: &str,
+    branch: &str,
+) -> Result<()> {
+    let branches = get_branches(client, configs, repo).await?;
+    if !branches.iter().any(|name| name == branch) {
+        bail!(
+            "Branch {branch} not found in {repo}\nAvailable branches: {}",
+            branches.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Get the deployment triggers of `service` in the linked environment
+pub async fn get_triggers(
+    client: &Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+    service: &str,
+) -> Result<Vec<Trigger>> {
+    let vars = queries::deployment_triggers::Variables {
+        project_id: linked_project.project.clone(),
+        environment_id: linked_project.environment.clone(),
+        service_id: service.to_owned(),
+    };
+    let res = post_graphql::<queries::DeploymentTriggers, _>(client, configs.get_backboard(), vars)
+        .await?;
+    let body = res.data.context("Failed to retrieve triggers")?;
+    Ok(body
+        .deployment_triggers
+        .edges
+        .into_iter()
+        .map(|edge| edge.node)
+        .collect())
+}
+
+/// Deploy `service` in the linked environment on pushes to `branch` of `repo`.
+/// Returns the ID of the new trigger.
+pub async fn create_trigger(
+    client: &Client,
+    configs: &Configs,
+    linked_project: &RailwayProject,
+    service: &str,
+    repo: &str,
+    branch: &str,
+    check_suites: bool,
+) -> Result<String> {
+    let vars = mutations::deployment_trigger_create::Variables {
+        project_id: linked_project.project.clone(),
+        environment_id: linked_project.environment.clone(),
+        service_id: service.to_owned(),
+        repository: repo.to_owned(),
+        branch: branch.to_owned(),
+        check_suites: Some(check_suites),
+    };
+    let res = post_graphql::<mutations::DeploymentTriggerCreate, _>(
+        client,
+        configs.get_backboard(),
+        vars,
+    )
+    .await?;
+    let body = res.data.context("Failed to create trigger")?;
+    Ok(body.deployment_trigger_create.id)
+}
+
+pub async fn update_trigger_branch(
+    client: &Client,
+    configs: &Configs,
+    id: &str,
+    branch: &str,
+) -> Result<()> {
+    let vars = mutations::deployment_trigger_update::Variables {
+        id: id.to_owned(),
+        branch: branch.to_owned(),
+    };
+    post_graphql::<mutations::DeploymentTriggerUpdate, _>(client, configs.get_backboard(), vars)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_trigger(client: &Client, configs: &Configs, id: &str) -> Result<()> {
+    let vars = mutations::deployment_trigger_delete::Variables { id: id.to_owned() };
+    post_graphql::<mutations::DeploymentTriggerDelete, _>(client, configs.get_backboard(), vars)
+        .await?;
+    Ok(())
+}
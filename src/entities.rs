@@ -6,4 +6,6 @@ pub struct UpResponse {
     pub url: String,
     pub logs_url: String,
     pub deployment_domain: String,
+    #[serde(default)]
+    pub deployment_id: Option<String>,
 }
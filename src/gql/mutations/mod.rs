@@ -55,3 +55,91 @@ pub struct LoginSessionConsume;
     response_derives = "Debug, Serialize, Clone"
 )]
 pub struct ServiceDomainCreate;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/DeploymentRedeploy.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct DeploymentRedeploy;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/DeploymentCancel.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct DeploymentCancel;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/ServiceInstanceRootDirectoryUpdate.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct ServiceInstanceRootDirectoryUpdate;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/DeploymentRestart.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct DeploymentRestart;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/DeploymentRollback.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct DeploymentRollback;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/DeploymentRemove.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct DeploymentRemove;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/DeploymentTriggerCreate.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct DeploymentTriggerCreate;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/DeploymentTriggerUpdate.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct DeploymentTriggerUpdate;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/DeploymentTriggerDelete.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct DeploymentTriggerDelete;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/ServiceInstanceSourceUpdate.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct ServiceInstanceSourceUpdate;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/mutations/strings/ServiceCreate.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct ServiceCreate;
@@ -2,6 +2,7 @@ use graphql_client::GraphQLQuery;
 
 type DateTime = chrono::DateTime<chrono::Utc>;
 type ServiceVariables = std::collections::BTreeMap<String, String>;
+type DeploymentMeta = serde_json::Value;
 
 #[derive(GraphQLQuery)]
 #[graphql(
@@ -70,23 +71,47 @@ pub struct Deployments;
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "src/gql/schema.graphql",
-    query_path = "src/gql/queries/strings/BuildLogs.graphql",
+    query_path = "src/gql/queries/strings/ServiceDomains.graphql",
     response_derives = "Debug, Serialize, Clone"
 )]
-pub struct BuildLogs;
+pub struct ServiceDomains;
 
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "src/gql/schema.graphql",
-    query_path = "src/gql/queries/strings/ServiceDomains.graphql",
+    query_path = "src/gql/queries/strings/ProjectToken.graphql",
     response_derives = "Debug, Serialize, Clone"
 )]
-pub struct ServiceDomains;
+pub struct ProjectToken;
 
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "src/gql/schema.graphql",
-    query_path = "src/gql/queries/strings/ProjectToken.graphql",
+    query_path = "src/gql/queries/strings/DeploymentSnapshotCodeUri.graphql",
     response_derives = "Debug, Serialize, Clone"
 )]
-pub struct ProjectToken;
+pub struct DeploymentSnapshotCodeUri;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/queries/strings/DeploymentTriggers.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct DeploymentTriggers;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/queries/strings/GitHubRepoBranches.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct GitHubRepoBranches;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/gql/schema.graphql",
+    query_path = "src/gql/queries/strings/GitHubEvents.graphql",
+    response_derives = "Debug, Serialize, Clone"
+)]
+pub struct GitHubEvents;
@@ -7,11 +7,13 @@ use commands::*;
 mod client;
 mod config;
 mod consts;
+mod controllers;
 mod entities;
 mod gql;
 mod subscription;
 mod table;
 mod tokio_spawner;
+mod util;
 
 #[macro_use]
 mod macros;
@@ -33,11 +35,16 @@ pub struct Args {
 // Specify the modules you want to include in the commands_enum! macro
 commands_enum!(
     add,
+    cancel,
     completion,
     delete,
+    deployments,
     domain,
+    down,
     docs,
     environment,
+    github,
+    ignore,
     init,
     link,
     list,
@@ -45,11 +52,15 @@ commands_enum!(
     logout,
     logs,
     open,
+    redeploy,
+    restart,
+    rollback,
     run,
     service,
     shell,
     starship,
     status,
+    triggers,
     unlink,
     up,
     variables,
@@ -22,8 +22,8 @@ impl Table {
 
         let max_right_content = self
             .rows
-            .iter()
-            .flat_map(|(_, content)| {
+            .values()
+            .flat_map(|content| {
                 content
                     .split('\n')
                     .map(console::measure_text_width)
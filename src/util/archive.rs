@@ -0,0 +1,690 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashSet},
+    hash::{Hash, Hasher},
+    io::{Read, Write},
+    path::{Component, Path, PathBuf},
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+};
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use sha2::{Digest, Sha256};
+use tar::{Archive, Builder, EntryType, HeaderMode};
+
+/// A file or directory that will be included in the upload
+#[derive(Clone)]
+pub struct Entry {
+    /// Path on disk
+    pub path: PathBuf,
+    /// Path inside the archive, relative to the upload root
+    pub name: PathBuf,
+}
+
+/// The walker configuration used to decide which files `railway up` uploads
+pub fn walk_builder(root: &Path, follow_symlinks: bool) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder.add_custom_ignore_filename(".railwayignore");
+    builder.follow_links(follow_symlinks).hidden(false);
+    builder
+}
+
+/// Collect the entries under `root`, sorted by their name in the archive so that
+/// the same tree always produces the same archive.
+///
+/// Unless `follow_symlinks` is set, symlinks are kept as symlinks and must point
+/// to somewhere inside `root`.
+///
+/// Directories are walked in parallel, so nothing is returned until the walk
+/// finishes. Use [`walk`] to archive entries as they are found.
+pub fn index(root: &Path, follow_symlinks: bool) -> Result<Vec<Entry>> {
+    let canonical_root = root
+        .canonicalize()
+        .with_context(|| format!("Unable to read {}", root.display()))?;
+    let (tx, rx) = mpsc::channel::<Result<Entry>>();
+    walk_builder(root, follow_symlinks)
+        .build_parallel()
+        .run(|| {
+            let tx = tx.clone();
+            let canonical_root = &canonical_root;
+            Box::new(move |entry| {
+                let result = visit(entry, root, canonical_root, follow_symlinks);
+                let failed = result.is_err();
+                if let Some(result) = result.transpose() {
+                    // The receiver outlives the walk, so this can't fail
+                    let _ = tx.send(result);
+                }
+                if failed {
+                    WalkState::Quit
+                } else {
+                    WalkState::Continue
+                }
+            })
+        });
+    drop(tx);
+
+    let mut entries = rx.into_iter().collect::<Result<Vec<_>>>()?;
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// How deep [`walk`] lists the tree by itself before handing each directory at that
+/// depth to a worker thread
+const SPLIT_DEPTH: usize = 2;
+
+/// Walk the entries under `root`, checking symlinks like [`index`] does, and return
+/// them as they are found.
+///
+/// Entries come in the same order as [`index`] returns them, so they can be archived
+/// while the walk is still going without affecting the archive's hash. To get there,
+/// the top of the tree is listed in order, and every directory [`SPLIT_DEPTH`] levels
+/// down is walked in order on a worker thread. Their entries are then read back in
+/// the order of the listing. With a single core, the tree is simply walked in order.
+pub fn walk(
+    root: &Path,
+    follow_symlinks: bool,
+) -> Result<Box<dyn Iterator<Item = Result<Entry>> + Send>> {
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    walk_on(root, follow_symlinks, threads)
+}
+
+/// [`walk`] with up to `threads` worker threads
+fn walk_on(
+    root: &Path,
+    follow_symlinks: bool,
+    threads: usize,
+) -> Result<Box<dyn Iterator<Item = Result<Entry>> + Send>> {
+    let canonical_root = root
+        .canonicalize()
+        .with_context(|| format!("Unable to read {}", root.display()))?;
+    if threads == 1 {
+        let root = root.to_path_buf();
+        let walker = sorted_walk_builder(&root, follow_symlinks).build();
+        return Ok(Box::new(walker.filter_map(move |entry| {
+            visit(entry, &root, &canonical_root, follow_symlinks).transpose()
+        })));
+    }
+
+    let mut listing = vec![];
+    let mut subtrees = vec![];
+    for entry in sorted_walk_builder(root, follow_symlinks)
+        .max_depth(Some(SPLIT_DEPTH))
+        .build()
+    {
+        let split = entry.as_ref().map_or(false, |entry| {
+            entry.depth() == SPLIT_DEPTH && entry.file_type().map_or(false, |t| t.is_dir())
+        });
+        let Some(result) = visit(entry, root, &canonical_root, follow_symlinks).transpose() else {
+            continue;
+        };
+        let subtree = match result {
+            Ok(ref entry) if split => {
+                let (tx, rx) = mpsc::channel();
+                subtrees.push((entry.name.clone(), tx));
+                Some(rx)
+            }
+            _ => None,
+        };
+        listing.push((result, subtree));
+    }
+
+    let threads = threads.min(subtrees.len());
+    let subtrees = Arc::new(Mutex::new(subtrees.into_iter()));
+    for _ in 0..threads {
+        let subtrees = subtrees.clone();
+        let root = root.to_path_buf();
+        let canonical_root = canonical_root.clone();
+        std::thread::spawn(move || loop {
+            // Subtrees are taken in the order they are read back, so the one being read
+            // always has a thread walking it
+            let next = subtrees.lock().unwrap().next();
+            let Some((name, tx)) = next else {
+                break;
+            };
+            walk_subtree(&root, &canonical_root, &name, follow_symlinks, &tx);
+        });
+    }
+
+    Ok(Box::new(listing.into_iter().flat_map(
+        |(result, subtree)| std::iter::once(result).chain(subtree.into_iter().flatten()),
+    )))
+}
+
+/// Walk everything below the directory `name`, which [`walk`] has already listed,
+/// sending the entries to `tx` in order
+fn walk_subtree(
+    root: &Path,
+    canonical_root: &Path,
+    name: &Path,
+    follow_symlinks: bool,
+    tx: &Sender<Result<Entry>>,
+) {
+    // Ignore files above `name` are picked up as parents of the walk
+    let walker = sorted_walk_builder(&root.join(name), follow_symlinks).build();
+    for entry in walker {
+        // The directory itself was listed already
+        if matches!(&entry, Ok(entry) if entry.depth() == 0) {
+            continue;
+        }
+        let Some(result) = visit(entry, root, canonical_root, follow_symlinks).transpose() else {
+            continue;
+        };
+        // The receiver is only dropped when the walk is abandoned
+        if tx.send(result).is_err() {
+            return;
+        }
+    }
+}
+
+/// Like [`walk_builder`], but visiting the children of each directory in order of their name
+fn sorted_walk_builder(root: &Path, follow_symlinks: bool) -> WalkBuilder {
+    let mut builder = walk_builder(root, follow_symlinks);
+    builder.sort_by_file_name(|a, b| a.cmp(b));
+    builder
+}
+
+/// Turn a walked entry into an archive entry, checking symlinks on the way
+fn visit(
+    entry: std::result::Result<DirEntry, ignore::Error>,
+    root: &Path,
+    canonical_root: &Path,
+    follow_symlinks: bool,
+) -> Result<Option<Entry>> {
+    let entry = match entry {
+        Ok(entry) => entry,
+        Err(err) if is_loop(&err) => {
+            bail!("{err}\nRemove the link or add it to .railwayignore to upload this directory")
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let name = entry.path().strip_prefix(root)?.to_path_buf();
+    // The root itself is implied by the archive
+    if name.as_os_str().is_empty() {
+        return Ok(None);
+    }
+    if entry.path_is_symlink() && !follow_symlinks {
+        check_symlink(entry.path(), &name, canonical_root)?;
+    }
+    Ok(Some(Entry {
+        path: entry.into_path(),
+        name,
+    }))
+}
+
+/// A cheap fingerprint of the entries under `root`, based on their names, sizes and
+/// modification times. Used to detect changes without reading every file.
+pub fn fingerprint(root: &Path, follow_symlinks: bool) -> Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    for entry in index(root, follow_symlinks)? {
+        let metadata = if follow_symlinks {
+            std::fs::metadata(&entry.path)
+        } else {
+            std::fs::symlink_metadata(&entry.path)
+        };
+        // Files removed between walking and reading simply drop out of the fingerprint
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        entry.name.hash(&mut hasher);
+        metadata.len().hash(&mut hasher);
+        metadata.modified().ok().hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
+
+/// Write a tarball of `entries` to `writer` and return the sha256 of the tarball.
+/// Timestamps, ownership and permissions are normalized, so the hash only changes
+/// when the uploaded content does. Writing stops at the first error in `entries`.
+pub fn write<W: Write>(
+    writer: W,
+    entries: impl IntoIterator<Item = Result<Entry>>,
+    follow_symlinks: bool,
+) -> Result<String> {
+    let mut archive = Builder::new(HashWriter::new(writer));
+    archive.mode(HeaderMode::Deterministic);
+    archive.follow_symlinks(follow_symlinks);
+    for entry in entries {
+        let entry = entry?;
+        archive.append_path_with_name(&entry.path, &entry.name)?;
+    }
+    let writer = archive.into_inner()?;
+    Ok(format!("{:x}", writer.hasher.finalize()))
+}
+
+/// Extract a gzipped tarball into `dest`, rejecting entries that would end up
+/// outside of it. Returns the number of files extracted.
+pub fn extract<R: Read>(reader: R, dest: &Path) -> Result<usize> {
+    let mut archive = Archive::new(GzDecoder::new(reader));
+    std::fs::create_dir_all(dest)?;
+    let mut files = 0;
+    // Symlinks extracted so far. The checks below are lexical, so a path that goes
+    // through one of them could end up anywhere.
+    let mut symlinks = HashSet::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.into_owned();
+        if !is_safe_name(&name) || passes_through(&name, &symlinks) {
+            bail!(
+                "Refusing to extract {}: it points outside of the archive",
+                name.display()
+            );
+        }
+        let entry_type = entry.header().entry_type();
+        match entry_type {
+            EntryType::Regular | EntryType::Continuous | EntryType::Directory => {}
+            EntryType::Symlink | EntryType::Link => {
+                let target = entry
+                    .link_name()?
+                    .with_context(|| format!("Link {} has no target", name.display()))?;
+                // Symlinks resolve relative to their directory, hard links to the archive root
+                let resolved = if entry_type == EntryType::Symlink {
+                    name.parent().unwrap_or(Path::new("")).join(&target)
+                } else {
+                    target.into_owned()
+                };
+                if !is_safe_name(&resolved) || passes_through(&resolved, &symlinks) {
+                    bail!(
+                        "Refusing to extract {}: it links outside of the archive",
+                        name.display()
+                    );
+                }
+            }
+            // Devices, fifos and other special files have no place in a source snapshot
+            _ => continue,
+        }
+        if !entry.unpack_in(dest)? {
+            bail!(
+                "Refusing to extract {}: it points outside of the archive",
+                name.display()
+            );
+        }
+        if entry_type.is_file() {
+            files += 1;
+        }
+        if entry_type == EntryType::Symlink {
+            symlinks.insert(normalize(&name));
+        }
+    }
+    Ok(files)
+}
+
+/// Read the regular files of a gzipped tarball into memory, keyed by their name
+pub fn read_files<R: Read>(reader: R) -> Result<BTreeMap<PathBuf, Vec<u8>>> {
+    let mut archive = Archive::new(GzDecoder::new(reader));
+    let mut files = BTreeMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        // Normalize away `./` so names from different archives compare equal
+        let name: PathBuf = entry
+            .path()?
+            .components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .collect();
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        files.insert(name, contents);
+    }
+    Ok(files)
+}
+
+/// Whether an archive entry name stays inside the archive
+fn is_safe_name(name: &Path) -> bool {
+    name.components().all(|component| {
+        matches!(
+            component,
+            Component::Normal(_) | Component::CurDir | Component::ParentDir
+        )
+    }) && !escapes_archive(name)
+}
+
+/// Whether resolving `path` inside the archive goes through one of `symlinks`
+fn passes_through(path: &Path, symlinks: &HashSet<PathBuf>) -> bool {
+    let mut current = PathBuf::new();
+    let mut components = path.components().peekable();
+    while let Some(component) = components.next() {
+        match component {
+            Component::Normal(part) => current.push(part),
+            Component::ParentDir => {
+                current.pop();
+            }
+            _ => {}
+        }
+        // The final component may be a symlink, it just can't be traversed
+        if components.peek().is_some() && symlinks.contains(&current) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Resolve `.` and `..` in a path inside the archive
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => {}
+        }
+    }
+    normalized
+}
+
+/// Make sure the symlink at `path` (named `name` in the archive) resolves to
+/// somewhere inside the project, so it can't pull in files from outside of it
+fn check_symlink(path: &Path, name: &Path, canonical_root: &Path) -> Result<()> {
+    let target = std::fs::read_link(path)?;
+    if target.is_absolute() {
+        bail!(
+            "Symlink {} has an absolute target {}\nUse a relative link, or pass --follow-symlinks to upload its contents",
+            name.display(),
+            target.display()
+        );
+    }
+
+    let escapes = match path.canonicalize() {
+        Ok(resolved) => !resolved.starts_with(canonical_root),
+        // Dangling links can't be resolved on disk, so resolve them inside the archive
+        Err(_) => escapes_archive(&name.parent().unwrap_or(Path::new("")).join(&target)),
+    };
+    if escapes {
+        bail!(
+            "Symlink {} points outside of the project ({})\nRemove the link, add it to .railwayignore, or pass --follow-symlinks to upload its contents",
+            name.display(),
+            target.display()
+        );
+    }
+    Ok(())
+}
+
+/// Whether a relative path inside the archive climbs above its root
+fn escapes_archive(path: &Path) -> bool {
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Component::ParentDir if depth == 0 => return true,
+            Component::ParentDir => depth -= 1,
+            Component::Normal(_) => depth += 1,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn is_loop(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. } => is_loop(err),
+        _ => false,
+    }
+}
+
+struct HashWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{write::GzEncoder, Compression};
+    use gzp::{deflate::Gzip, ZBuilder};
+    use tar::Header;
+
+    use super::*;
+
+    /// Build a gzipped tarball from raw `(name, type, link target)` entries, bypassing
+    /// the checks `tar::Builder` does on names so that malicious archives can be made
+    fn tarball(entries: &[(&str, EntryType, &str)]) -> Vec<u8> {
+        let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, entry_type, target) in entries {
+            let mut header = Header::new_gnu();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_entry_type(*entry_type);
+            header.set_mode(0o644);
+            let contents: &[u8] = if *entry_type == EntryType::Regular {
+                b"contents"
+            } else {
+                header.set_link_name(target).unwrap();
+                b""
+            };
+            header.set_size(contents.len() as u64);
+            header.set_cksum();
+            builder.append(&header, contents).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    /// Extract into a directory nested in a scratch directory, returning the error and
+    /// whether anything was written next to the destination
+    fn extract_nested(entries: &[(&str, EntryType, &str)]) -> (Result<usize>, bool) {
+        let scratch = tempfile::tempdir().unwrap();
+        let dest = scratch.path().join("dest");
+        let result = extract(tarball(entries).as_slice(), &dest);
+        let escaped = std::fs::read_dir(scratch.path()).unwrap().count() > 1;
+        (result, escaped)
+    }
+
+    #[test]
+    fn extracts_regular_files_and_inner_links() {
+        let scratch = tempfile::tempdir().unwrap();
+        let archive = tarball(&[
+            ("src/main.rs", EntryType::Regular, ""),
+            ("src/lib.rs", EntryType::Symlink, "main.rs"),
+            ("main.rs", EntryType::Link, "src/main.rs"),
+        ]);
+        assert_eq!(extract(archive.as_slice(), scratch.path()).unwrap(), 1);
+        for name in ["src/lib.rs", "main.rs"] {
+            assert_eq!(
+                std::fs::read(scratch.path().join(name)).unwrap(),
+                b"contents"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_absolute_paths() {
+        let (result, escaped) = extract_nested(&[("/tmp/evil", EntryType::Regular, "")]);
+        assert!(result.is_err());
+        assert!(!escaped);
+    }
+
+    #[test]
+    fn rejects_parent_paths() {
+        let (result, escaped) = extract_nested(&[("../evil", EntryType::Regular, "")]);
+        assert!(result.is_err());
+        assert!(!escaped);
+    }
+
+    #[test]
+    fn rejects_escaping_symlinks() {
+        for target in ["..", "../evil", "/etc/passwd", "a/../../evil"] {
+            let (result, escaped) = extract_nested(&[("link", EntryType::Symlink, target)]);
+            assert!(result.is_err(), "{target} was extracted");
+            assert!(!escaped);
+        }
+    }
+
+    #[test]
+    fn rejects_escaping_hard_links() {
+        for target in ["../evil", "/etc/passwd"] {
+            let (result, escaped) = extract_nested(&[("link", EntryType::Link, target)]);
+            assert!(result.is_err(), "{target} was extracted");
+            assert!(!escaped);
+        }
+    }
+
+    #[test]
+    fn rejects_symlink_chains() {
+        // Lexically `a/b -> ..` stays inside, but `a` points at the root so it doesn't
+        let (result, escaped) = extract_nested(&[
+            ("a", EntryType::Symlink, "."),
+            ("a/b", EntryType::Symlink, ".."),
+        ]);
+        assert!(result.is_err());
+        assert!(!escaped);
+
+        let (result, escaped) = extract_nested(&[
+            ("a", EntryType::Symlink, "."),
+            ("c", EntryType::Symlink, "a/.."),
+        ]);
+        assert!(result.is_err());
+        assert!(!escaped);
+    }
+
+    #[test]
+    fn checks_names_lexically() {
+        assert!(!escapes_archive(Path::new("a/b/../c")));
+        assert!(!escapes_archive(Path::new("a/..")));
+        assert!(escapes_archive(Path::new("..")));
+        assert!(escapes_archive(Path::new("a/../../b")));
+
+        assert!(is_safe_name(Path::new("./a/b")));
+        assert!(is_safe_name(Path::new("a/../b")));
+        assert!(!is_safe_name(Path::new("/a")));
+        assert!(!is_safe_name(Path::new("../a")));
+    }
+
+    #[test]
+    fn archives_are_deterministic() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("src/nested")).unwrap();
+        std::fs::write(root.path().join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.path().join("src/nested/mod.rs"), "").unwrap();
+        std::fs::write(root.path().join("README.md"), "# Readme").unwrap();
+
+        let hash = || {
+            let entries = index(root.path(), false).unwrap();
+            write(std::io::sink(), entries.into_iter().map(Ok), false).unwrap()
+        };
+        let first = hash();
+        // Rewriting a file with the same contents only changes its modification time
+        std::fs::write(root.path().join("README.md"), "# Readme").unwrap();
+        assert_eq!(first, hash());
+
+        std::fs::write(root.path().join("README.md"), "# Changed").unwrap();
+        assert_ne!(first, hash());
+    }
+
+    /// Create a tree of `dirs` packages, each holding `files` files, with mixed names
+    /// and depths so that sorting per directory has to agree with sorting full names
+    fn package_tree(dirs: usize, files: usize) -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join(".railwayignore"),
+            "*.log\n/packages/package1/src/nested-dir/file1.rs\n",
+        )
+        .unwrap();
+        for dir in 0..dirs {
+            let package = root.path().join(format!("packages/package{dir}"));
+            let src = package.join("src/nested-dir");
+            std::fs::create_dir_all(&src).unwrap();
+            std::fs::write(package.join(".railwayignore"), "dist\n").unwrap();
+            std::fs::create_dir_all(package.join("dist")).unwrap();
+            std::fs::write(package.join("dist/bundle.js"), "ignored").unwrap();
+            std::fs::write(package.join("debug.log"), "ignored").unwrap();
+            std::fs::write(src.with_extension("rs"), "").unwrap();
+            for file in 0..files {
+                let contents = format!("pub const FILE: usize = {file};\n").repeat(50);
+                std::fs::write(src.join(format!("file{file}.rs")), contents).unwrap();
+            }
+        }
+        std::fs::write(root.path().join("packages/.railwayignore"), "/package2\n").unwrap();
+        std::fs::write(root.path().join("packages.json"), "{}").unwrap();
+        root
+    }
+
+    #[test]
+    fn walking_matches_indexing() {
+        let root = package_tree(20, 20);
+        let indexed = index(root.path(), false).unwrap();
+        let names = |entries: &[Entry]| entries.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+        assert!(!names(&indexed).iter().any(|name| {
+            name.starts_with("packages/package0/dist")
+                || name.starts_with("packages/package2")
+                || name.ends_with("debug.log")
+                || name.ends_with("package1/src/nested-dir/file1.rs")
+        }));
+        assert!(
+            names(&indexed).contains(&PathBuf::from("packages/package0/src/nested-dir/file1.rs"))
+        );
+        let hash = write(std::io::sink(), indexed.iter().cloned().map(Ok), false).unwrap();
+
+        // Both walking the tree in one go and splitting it up between threads
+        for threads in [1, 4] {
+            let walked = walk_on(root.path(), false, threads)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(names(&walked), names(&indexed));
+            assert_eq!(
+                write(std::io::sink(), walked.into_iter().map(Ok), false).unwrap(),
+                hash
+            );
+        }
+    }
+
+    /// Compares compressing once the whole tree is indexed with compressing while walking,
+    /// the way `railway up` does. The speedup needs more than one core.
+    /// Run with `cargo test --release -- --ignored archiving_while_walking_is_faster`.
+    #[test]
+    #[ignore]
+    fn archiving_while_walking_is_faster() {
+        let root = package_tree(400, 250);
+        let compress = |entries: Box<dyn Iterator<Item = Result<Entry>> + Send>| {
+            let mut parz = ZBuilder::<Gzip, _>::new()
+                .num_threads(num_cpus::get())
+                .from_writer(std::io::sink());
+            let hash = write(&mut parz, entries, false).unwrap();
+            parz.finish().unwrap();
+            hash
+        };
+
+        let started = std::time::Instant::now();
+        let indexed = index(root.path(), false).unwrap();
+        let collected_hash = compress(Box::new(indexed.into_iter().map(Ok)));
+        let collected = started.elapsed();
+
+        let started = std::time::Instant::now();
+        let walked_hash = compress(walk(root.path(), false).unwrap());
+        let pipelined = started.elapsed();
+
+        assert_eq!(collected_hash, walked_hash);
+        if num_cpus::get() > 1 {
+            assert!(
+                pipelined < collected,
+                "archiving while walking took {pipelined:?}, indexing first took {collected:?}"
+            );
+        }
+    }
+}
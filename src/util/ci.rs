@@ -0,0 +1,51 @@
+use std::{fs::OpenOptions, io::Write};
+
+use anyhow::Result;
+
+/// Whether we're running in CI, where output should be plain and log friendly
+pub fn is_ci() -> bool {
+    is_set("CI") || is_github_actions()
+}
+
+pub fn is_github_actions() -> bool {
+    is_set("GITHUB_ACTIONS")
+}
+
+fn is_set(name: &str) -> bool {
+    matches!(std::env::var(name), Ok(value) if !value.is_empty() && value != "false" && value != "0")
+}
+
+/// Start a collapsible group of log lines in GitHub Actions
+pub fn start_group(title: &str) {
+    if is_github_actions() {
+        println!("::group::{title}");
+    }
+}
+
+pub fn end_group() {
+    if is_github_actions() {
+        println!("::endgroup::");
+    }
+}
+
+/// Annotate the GitHub Actions run with an error
+pub fn error(message: &str) {
+    if is_github_actions() {
+        // Annotations are a single line, newlines have to be escaped
+        let message = message
+            .replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A");
+        println!("::error title=Railway::{message}");
+    }
+}
+
+/// Append markdown to the summary of the current GitHub Actions step
+pub fn write_summary(markdown: &str) -> Result<()> {
+    let Ok(path) = std::env::var("GITHUB_STEP_SUMMARY") else {
+        return Ok(());
+    };
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{markdown}")?;
+    Ok(())
+}
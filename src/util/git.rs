@@ -0,0 +1,49 @@
+use std::{path::Path, process::Command};
+
+use serde::Serialize;
+
+/// Information about the git commit checked out in an uploaded directory
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitMetadata {
+    pub commit_hash: String,
+    pub commit_message: String,
+    /// `None` when HEAD is detached
+    pub branch: Option<String>,
+    /// Whether the working tree has uncommitted changes
+    pub dirty: bool,
+}
+
+impl GitMetadata {
+    /// Read the metadata of the repository containing `path`.
+    /// Returns `None` if `path` isn't inside a git repository or git isn't installed.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let commit_hash = git(path, &["rev-parse", "HEAD"])?;
+        let commit_message = git(path, &["log", "-1", "--format=%B"])?;
+        let branch = git(path, &["rev-parse", "--abbrev-ref", "HEAD"]).filter(|b| b != "HEAD");
+        let dirty = !git(path, &["status", "--porcelain"])?.is_empty();
+        Some(Self {
+            commit_hash,
+            commit_message,
+            branch,
+            dirty,
+        })
+    }
+
+    pub fn short_hash(&self) -> &str {
+        &self.commit_hash[..self.commit_hash.len().min(7)]
+    }
+}
+
+fn git(path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
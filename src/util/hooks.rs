@@ -0,0 +1,67 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+
+/// Where hooks are declared, relative to the directory `railway up` uploads
+pub const HOOKS_PATH: &str = ".railway/hooks.json";
+
+/// Commands to run around `railway up`
+#[derive(Debug, Default, Deserialize)]
+pub struct Hooks {
+    /// Run before indexing. A failing command aborts the upload.
+    #[serde(default)]
+    pub pre_up: Vec<String>,
+    /// Run after the deployment succeeds
+    #[serde(default)]
+    pub post_up: Vec<String>,
+}
+
+impl Hooks {
+    /// Read the hooks declared for the project in `root`, if any
+    pub fn from_path(root: &Path) -> Result<Self> {
+        let path = root.join(HOOKS_PATH);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Unable to read {}", path.display()))
+            }
+        };
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid hooks in {}", path.display()))
+    }
+}
+
+/// Run each of `commands` with the platform shell in `dir`, stopping at the first one
+/// that fails
+pub async fn run(commands: &[String], dir: &Path, envs: &[(&str, &str)]) -> Result<()> {
+    for command in commands {
+        println!("{} {}", "Running hook".bold(), command.dimmed());
+        let status = shell(command)
+            .current_dir(dir)
+            .envs(envs.iter().copied())
+            .status()
+            .await
+            .with_context(|| format!("Failed to spawn hook `{command}`"))?;
+        if !status.success() {
+            bail!("Hook `{command}` failed with {status}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
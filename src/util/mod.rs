@@ -0,0 +1,6 @@
+pub mod archive;
+pub mod ci;
+pub mod git;
+pub mod hooks;
+pub mod prompt;
+pub mod secrets;
@@ -0,0 +1,28 @@
+use anyhow::{bail, Result};
+use is_terminal::IsTerminal;
+
+use crate::{
+    config::Configs,
+    consts::{ABORTED_BY_USER, CONFIRMATION_REQUIRED},
+};
+
+use super::ci;
+
+/// Ask the user to confirm an action, unless `yes` was passed.
+/// Without an interactive terminal, or in CI, `yes` is required.
+pub fn confirm(configs: &Configs, message: &str, yes: bool) -> Result<()> {
+    if yes {
+        return Ok(());
+    }
+    if !std::io::stdout().is_terminal() || ci::is_ci() {
+        bail!(CONFIRMATION_REQUIRED);
+    }
+    let confirmed = inquire::Confirm::new(message)
+        .with_render_config(configs.get_render_config())
+        .with_default(false)
+        .prompt()?;
+    if !confirmed {
+        bail!(ABORTED_BY_USER);
+    }
+    Ok(())
+}
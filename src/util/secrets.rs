@@ -0,0 +1,152 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use regex::Regex;
+
+use super::archive::Entry;
+
+/// Lines in the root .railwayignore starting with this allow a gitignore-style pattern
+/// to be uploaded even if it looks like it contains secrets
+pub const ALLOW_SECRET_PREFIX: &str = "# railway:allow-secret ";
+
+/// Files larger than this are not scanned for secret patterns
+const MAX_SCAN_SIZE: u64 = 1024 * 1024;
+
+const SECRET_FILE_NAMES: &[(&str, &str)] = &[
+    (".env", "environment file"),
+    (".pypirc", "PyPI credentials"),
+    (".netrc", "netrc credentials"),
+    (".git-credentials", "git credentials"),
+    ("id_rsa", "SSH private key"),
+    ("id_dsa", "SSH private key"),
+    ("id_ecdsa", "SSH private key"),
+    ("id_ed25519", "SSH private key"),
+];
+
+const SECRET_EXTENSIONS: &[(&str, &str)] = &[
+    ("pem", "PEM certificate or key"),
+    ("key", "private key"),
+    ("p12", "PKCS#12 keystore"),
+    ("pfx", "PKCS#12 keystore"),
+];
+
+/// `.env.*` files that conventionally don't contain real values
+const ENV_TEMPLATES: &[&str] = &[".env.example", ".env.sample", ".env.template"];
+
+const SECRET_PATTERNS: &[(&str, &str)] = &[
+    (
+        r"-----BEGIN ((RSA|DSA|EC|OPENSSH|ENCRYPTED|PGP) )?PRIVATE KEY( BLOCK)?-----",
+        "private key",
+    ),
+    (r"\b(AKIA|ASIA)[0-9A-Z]{16}\b", "AWS access key"),
+    (
+        r#"RAILWAY_(API_)?TOKEN\s*[=:]\s*["']?[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}"#,
+        "Railway token",
+    ),
+    (r"(?m)^\s*//.+/:_authToken\s*=\s*[^\s$]", "npm auth token"),
+];
+
+/// A file that looks like it contains secrets
+pub struct Finding {
+    pub name: PathBuf,
+    pub reason: &'static str,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name.display(), self.reason)
+    }
+}
+
+/// Look for well-known secret files and high-confidence secret patterns in the
+/// entries that are about to be uploaded from `root`.
+/// With `follow_symlinks`, symlinks are scanned as the files they point to, since
+/// that is what gets uploaded.
+pub fn scan(
+    root: &Path,
+    entries: impl IntoIterator<Item = Entry>,
+    follow_symlinks: bool,
+) -> Result<Vec<Finding>> {
+    let allowed = allowlist(root)?;
+    let patterns = SECRET_PATTERNS
+        .iter()
+        .map(|(pattern, reason)| Ok((Regex::new(pattern)?, *reason)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut findings = vec![];
+    for entry in entries {
+        if allowed
+            .matched_path_or_any_parents(&entry.name, false)
+            .is_ignore()
+        {
+            continue;
+        }
+        if let Some(reason) = match_name(&entry.name) {
+            findings.push(Finding {
+                name: entry.name.clone(),
+                reason,
+            });
+            continue;
+        }
+        let metadata = if follow_symlinks {
+            std::fs::metadata(&entry.path)?
+        } else {
+            std::fs::symlink_metadata(&entry.path)?
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        if metadata.len() > MAX_SCAN_SIZE {
+            continue;
+        }
+        let mut contents = vec![];
+        File::open(&entry.path)?.read_to_end(&mut contents)?;
+        // Binary files are unlikely to hold secrets we can recognize
+        if contents.contains(&0) {
+            continue;
+        }
+        let contents = String::from_utf8_lossy(&contents);
+        if let Some((_, reason)) = patterns.iter().find(|(regex, _)| regex.is_match(&contents)) {
+            findings.push(Finding {
+                name: entry.name.clone(),
+                reason,
+            });
+        }
+    }
+    Ok(findings)
+}
+
+fn match_name(name: &Path) -> Option<&'static str> {
+    let file_name = name.file_name()?.to_str()?;
+    if file_name.starts_with(".env.") && !ENV_TEMPLATES.contains(&file_name) {
+        return Some("environment file");
+    }
+    if let Some((_, reason)) = SECRET_FILE_NAMES.iter().find(|(n, _)| *n == file_name) {
+        return Some(reason);
+    }
+    let extension = name.extension()?.to_str()?;
+    SECRET_EXTENSIONS
+        .iter()
+        .find(|(e, _)| e.eq_ignore_ascii_case(extension))
+        .map(|(_, reason)| *reason)
+}
+
+/// Build a matcher from the allow entries in the root .railwayignore
+fn allowlist(root: &Path) -> Result<Gitignore> {
+    // Match against archive names, which are relative to the root
+    let mut builder = GitignoreBuilder::new("");
+    if let Ok(contents) = std::fs::read_to_string(root.join(".railwayignore")) {
+        for line in contents.lines() {
+            if let Some(pattern) = line.strip_prefix(ALLOW_SECRET_PREFIX) {
+                builder.add_line(None, pattern.trim())?;
+            }
+        }
+    }
+    Ok(builder.build()?)
+}